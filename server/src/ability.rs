@@ -0,0 +1,265 @@
+/*
+ * File: ability.rs
+ *
+ * Copyright (c) 2025 Alan Fung
+ *
+ * Description: reads the damage and cooldowns of champion abilities from the champion data
+ */
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::combat::{Attacker, DamageType};
+use crate::skills::ABILITY_NAMES;
+use crate::stats::StatKind;
+
+/// what one part of an ability's damage scales with
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Scaling {
+    Flat,
+    AttackDamage,
+    BonusAttackDamage,
+    AbilityPower,
+    Health,
+    BonusHealth,
+    Armor,
+    BonusArmor,
+    MagicResistance,
+    BonusMagicResistance,
+    TargetMaxHealth,
+    TargetCurrentHealth,
+    TargetMissingHealth,
+}
+
+impl Scaling {
+    /// Reads a unit from the ability data, such as "% bonus AD". Percent scalings are returned
+    /// with the factor that turns their values into fractions.
+    fn from_unit(unit: &str) -> Option<(Scaling, f64)> {
+        let unit = unit.trim();
+        if unit.is_empty() {
+            return Some((Scaling::Flat, 1.0));
+        }
+
+        let scaling = match unit.strip_prefix('%')?.trim() {
+            "AD" => Scaling::AttackDamage,
+            "bonus AD" => Scaling::BonusAttackDamage,
+            "AP" => Scaling::AbilityPower,
+            "maximum health" => Scaling::Health,
+            "bonus health" => Scaling::BonusHealth,
+            "armor" => Scaling::Armor,
+            "bonus armor" => Scaling::BonusArmor,
+            "magic resistance" => Scaling::MagicResistance,
+            "bonus magic resistance" => Scaling::BonusMagicResistance,
+            "of target's maximum health" => Scaling::TargetMaxHealth,
+            "of target's current health" => Scaling::TargetCurrentHealth,
+            "of target's missing health" => Scaling::TargetMissingHealth,
+            _ => return None,
+        };
+        Some((scaling, 0.01))
+    }
+}
+
+/// stores one part of an ability's damage, with a value for each rank
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Modifier {
+    pub scaling: Scaling,
+    /// values by rank, or by level for the few abilities that scale with level instead
+    pub values: Vec<f64>,
+}
+
+impl Modifier {
+    /// Picks the value for a rank of the ability, or for the champion's level when there is a
+    /// value for every level.
+    pub fn value(&self, rank: u8, level: u8) -> f64 {
+        let index = if self.values.len() == 18 { level } else { rank };
+        let index = (index.max(1) as usize - 1).min(self.values.len().saturating_sub(1));
+        self.values.get(index).copied().unwrap_or(0.0)
+    }
+}
+
+/// stores what an ability does that matters for damage calculations
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Ability {
+    pub name: String,
+    pub damage_type: DamageType,
+    /// parts of the damage that are summed for one cast, which is empty for abilities without
+    /// damage
+    pub damage: Vec<Modifier>,
+    /// cooldown in seconds by rank
+    pub cooldown: Vec<f64>,
+    /// whether ability haste shortens the cooldown
+    pub affected_by_haste: bool,
+}
+
+impl Ability {
+    /// Computes the damage of one cast at a rank before resistances, given the target's current
+    /// and maximum health.
+    pub fn raw_damage(&self, attacker: &Attacker, rank: u8, current_health: f64, max_health: f64) -> f64 {
+        let current = current_health.max(0.0);
+        let bonus = |kind: StatKind| attacker.stats[kind] - attacker.base_value(kind);
+
+        self.damage.iter()
+            .map(|modifier| {
+                let scaled = match modifier.scaling {
+                    Scaling::Flat => 1.0,
+                    Scaling::AttackDamage => attacker.stats[StatKind::AttackDamage],
+                    Scaling::BonusAttackDamage => bonus(StatKind::AttackDamage),
+                    Scaling::AbilityPower => attacker.stats[StatKind::AbilityPower],
+                    Scaling::Health => attacker.stats[StatKind::Health],
+                    Scaling::BonusHealth => bonus(StatKind::Health),
+                    Scaling::Armor => attacker.stats[StatKind::Armor],
+                    Scaling::BonusArmor => bonus(StatKind::Armor),
+                    Scaling::MagicResistance => attacker.stats[StatKind::MagicResistance],
+                    Scaling::BonusMagicResistance => bonus(StatKind::MagicResistance),
+                    Scaling::TargetMaxHealth => max_health,
+                    Scaling::TargetCurrentHealth => current,
+                    Scaling::TargetMissingHealth => (max_health - current).max(0.0),
+                };
+                modifier.value(rank, attacker.level) * scaled
+            })
+            .sum()
+    }
+}
+
+/// Reads a damage type from the name of an effect, such as "Bonus Physical Damage", falling back
+/// to the damage type of the whole ability.
+fn damage_type(attribute: &str, ability: &Value) -> DamageType {
+    let ability_type = ability.get("damageType").and_then(|v| v.as_str()).unwrap_or("");
+    if attribute.contains("True") || (!attribute.contains("Magic") && !attribute.contains("Physical") && ability_type == "TRUE_DAMAGE") {
+        DamageType::True
+    } else if attribute.contains("Magic") || (!attribute.contains("Physical") && ability_type == "MAGIC_DAMAGE") {
+        DamageType::Magic
+    } else {
+        DamageType::Physical
+    }
+}
+
+/// Checks whether an effect is damage dealt, rather than something like damage reduction.
+fn is_damage(attribute: &str) -> bool {
+    attribute.contains("Damage") && !["Reduction", "Increased", "Amplification"].iter().any(|word| attribute.contains(word))
+}
+
+/// Reads the values of a list of modifiers. Modifiers in units that cannot be calculated are
+/// skipped.
+fn parse_modifiers(modifiers: &Value) -> Vec<Modifier> {
+    modifiers.as_array()
+        .map(|modifiers| modifiers.iter()
+            .filter_map(|modifier| {
+                let unit = modifier.pointer("/units/0").and_then(|v| v.as_str()).unwrap_or("");
+                let (scaling, factor) = Scaling::from_unit(unit)?;
+                let values = modifier.get("values")?.as_array()?
+                    .iter()
+                    .map(|value| value.as_f64().unwrap_or(0.0) * factor)
+                    .collect();
+                Some(Modifier { scaling, values })
+            })
+            .collect())
+        .unwrap_or_default()
+}
+
+/// Reads an ability from its entry in the champion data. Only the first damage value listed is
+/// used, so abilities that hit several times or deal damage in parts count as a single hit.
+pub fn parse_ability(ability: &Value) -> Option<Ability> {
+    let name = ability.get("name")?.as_str()?.to_string();
+
+    let leveling = ability.get("effects")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|effect| effect.get("leveling").and_then(|v| v.as_array()))
+        .flatten()
+        .find(|leveling| leveling.get("attribute").and_then(|v| v.as_str()).is_some_and(is_damage));
+
+    let (damage_type, damage) = match leveling {
+        Some(leveling) => {
+            let attribute = leveling.get("attribute").and_then(|v| v.as_str()).unwrap_or("");
+            let damage = leveling.get("modifiers").map(parse_modifiers).unwrap_or_default();
+            (damage_type(attribute, ability), damage)
+        }
+        None => (damage_type("", ability), Vec::new()),
+    };
+
+    let cooldown = ability.get("cooldown");
+    Some(Ability {
+        name,
+        damage_type,
+        damage,
+        cooldown: cooldown
+            .and_then(|cooldown| cooldown.pointer("/modifiers/0/values"))
+            .and_then(|v| v.as_array())
+            .map(|values| values.iter().filter_map(|value| value.as_f64()).collect())
+            .unwrap_or_default(),
+        affected_by_haste: cooldown
+            .and_then(|cooldown| cooldown.get("affectedByCdr"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true),
+    })
+}
+
+/// Reads a champion's Q, W, E and R from its entry in the champion data. Abilities with several
+/// forms use the first one.
+pub fn parse_abilities(champion: &Value) -> [Option<Ability>; 4] {
+    ABILITY_NAMES.map(|key| champion.pointer(&format!("/abilities/{}/0", key)).and_then(parse_ability))
+}
+
+/// Returns the cooldown of an ability at a rank after ability haste, or None when the data has
+/// no cooldown for the ability.
+pub fn cooldown(ability: &Ability, rank: u8, ability_haste: f64) -> Option<f64> {
+    let index = (rank.max(1) as usize - 1).min(ability.cooldown.len().saturating_sub(1));
+    let cooldown = *ability.cooldown.get(index)?;
+    if ability.affected_by_haste {
+        Some(cooldown * 100.0 / (100.0 + ability_haste.max(0.0)))
+    } else {
+        Some(cooldown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reads_damage_and_cooldowns() {
+        let ability = json!({
+            "name": "Decisive Strike",
+            "damageType": "PHYSICAL_DAMAGE",
+            "effects": [
+                { "leveling": [] },
+                { "leveling": [{ "attribute": "Bonus Physical Damage", "modifiers": [
+                    { "values": [30, 60, 90, 120, 150], "units": ["", "", "", "", ""] },
+                    { "values": [50, 50, 50, 50, 50], "units": ["% AD", "% AD", "% AD", "% AD", "% AD"] },
+                    { "values": [1, 1, 1, 1, 1], "units": ["% per 100 AP", "", "", "", ""] }
+                ] }] }
+            ],
+            "cooldown": { "modifiers": [{ "values": [8, 8, 8, 8, 8] }], "affectedByCdr": true }
+        });
+
+        let ability = parse_ability(&ability).unwrap();
+        assert_eq!(ability.damage_type, DamageType::Physical);
+        assert_eq!(ability.damage.len(), 2);
+        assert_eq!(ability.damage[0].value(3, 1), 90.0);
+        assert_eq!(ability.damage[1].scaling, Scaling::AttackDamage);
+        assert_eq!(ability.damage[1].value(1, 1), 0.5);
+        assert_eq!(cooldown(&ability, 2, 100.0), Some(4.0));
+        assert_eq!(cooldown(&Ability { cooldown: Vec::new(), ..ability }, 2, 100.0), None);
+    }
+
+    #[test]
+    fn damage_types_come_from_the_effect_first() {
+        let ability = json!({ "damageType": "PHYSICAL_DAMAGE" });
+        assert_eq!(damage_type("Magic Damage", &ability), DamageType::Magic);
+        assert_eq!(damage_type("True Damage", &ability), DamageType::True);
+        assert_eq!(damage_type("Damage", &json!({ "damageType": "MAGIC_DAMAGE" })), DamageType::Magic);
+        assert!(!is_damage("Damage Reduction"));
+    }
+
+    #[test]
+    fn level_scaling_values_use_the_level() {
+        let modifier = Modifier { scaling: Scaling::Flat, values: (1..=18).map(f64::from).collect() };
+        assert_eq!(modifier.value(1, 11), 11.0);
+        let modifier = Modifier { scaling: Scaling::Flat, values: vec![1.0, 2.0, 3.0] };
+        assert_eq!(modifier.value(5, 11), 3.0);
+    }
+}
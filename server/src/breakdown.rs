@@ -0,0 +1,360 @@
+/*
+ * File: breakdown.rs
+ *
+ * Copyright (c) 2025 Alan Fung
+ *
+ * Description: resolves stats from their sources while recording where every point came from
+ */
+use serde::Serialize;
+
+use crate::stats::{self, ResolvedStats, StatKind, StatUnit, Stats};
+
+/// kinds of sources that can contribute to a stat
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SourceKind {
+    /// the champion's base stats
+    Champion,
+    /// growth from the champion's level
+    Level,
+    Item,
+    /// item passives missing from the item data, such as Rabadon's Deathcap
+    Passive,
+    /// stats stored on the player directly
+    Bonus,
+    /// the amount removed by a stat cap
+    Cap,
+}
+
+/// stores the stats granted by a single source
+#[derive(Debug, Clone)]
+pub struct Source {
+    pub kind: SourceKind,
+    pub id: String,
+    pub name: String,
+    pub stats: Stats,
+}
+
+/// stores a single contribution to a stat, where amount is value times multiplier
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Contribution {
+    pub source: SourceKind,
+    pub id: String,
+    pub name: String,
+    pub value: f64,
+    pub multiplier: f64,
+    pub amount: f64,
+}
+
+/// stores a stat's total along with every contribution to it, in the order they are applied
+#[derive(Serialize, Debug, Clone)]
+pub struct StatBreakdown {
+    pub stat: StatKind,
+    pub total: f64,
+    pub contributions: Vec<Contribution>,
+}
+
+impl Contribution {
+    fn new(source: &Source, kind: SourceKind, value: f64, multiplier: f64) -> Self {
+        Contribution {
+            source: kind,
+            id: source.id.clone(),
+            name: source.name.clone(),
+            value,
+            multiplier,
+            amount: value * multiplier,
+        }
+    }
+}
+
+/// Sums the stats of every source other than the champion.
+pub fn bonus_stats(sources: &[Source]) -> Stats {
+    let mut bonus = Stats::new();
+    for source in sources.iter().filter(|source| source.kind != SourceKind::Champion) {
+        bonus += &source.stats;
+    }
+    bonus
+}
+
+/// Sums the stats of the champion sources.
+pub fn base_stats(sources: &[Source]) -> Stats {
+    let mut base = Stats::new();
+    for source in sources.iter().filter(|source| source.kind == SourceKind::Champion) {
+        base += &source.stats;
+    }
+    base
+}
+
+/// Resolves every stat at a given level, recording each contribution.
+pub fn explain(sources: &[Source], level: u8, champion: &str) -> Vec<StatBreakdown> {
+    let mut breakdowns: Vec<StatBreakdown> = StatKind::ALL
+        .into_iter()
+        .map(|kind| {
+            let contributions = match kind {
+                StatKind::AttackSpeed => explain_attack_speed(sources, level, champion),
+                _ => explain_stat(kind, sources, level),
+            };
+            StatBreakdown {
+                stat: kind,
+                total: contributions.iter().map(|contribution| contribution.amount).sum(),
+                contributions,
+            }
+        })
+        .collect();
+    apply_attack_speed_conversion(&mut breakdowns, sources, level, champion);
+    apply_adaptive_force(&mut breakdowns);
+    breakdowns
+}
+
+/// Resolves every stat at a given level.
+pub fn resolve(sources: &[Source], level: u8, champion: &str) -> ResolvedStats {
+    let mut resolved = ResolvedStats::new();
+    for breakdown in explain(sources, level, champion) {
+        resolved[breakdown.stat] = breakdown.total;
+    }
+    resolved
+}
+
+/// Flat values and level growth are summed first. Percent stats add their percent directly, while
+/// the percent of other stats scales the summed total.
+fn explain_stat(kind: StatKind, sources: &[Source], level: u8) -> Vec<Contribution> {
+    let growth = stats::growth(level);
+    let mut contributions = Vec::new();
+
+    for source in sources {
+        let stat = &source.stats[kind];
+        contributions.push(Contribution::new(source, source.kind, stat.flat, 1.0));
+
+        let growth_kind = match source.kind {
+            SourceKind::Champion => SourceKind::Level,
+            other => other,
+        };
+        contributions.push(Contribution::new(source, growth_kind, stat.per_level, growth));
+
+        if kind.unit() == StatUnit::Percent {
+            contributions.push(Contribution::new(source, source.kind, stat.percent, 1.0));
+        }
+    }
+
+    if kind.percent_scales_total() {
+        let subtotal: f64 = contributions.iter().map(|contribution| contribution.amount).sum();
+        for source in sources {
+            let percent = source.stats[kind].percent;
+            contributions.push(Contribution::new(source, source.kind, percent, subtotal / 100.0));
+        }
+    }
+
+    contributions.retain(|contribution| contribution.value != 0.0);
+    contributions
+}
+
+/// Champions like Jhin gain a percent of their attack damage for every percent of bonus attack
+/// speed, recorded as a passive contribution to attack damage.
+fn apply_attack_speed_conversion(breakdowns: &mut [StatBreakdown], sources: &[Source], level: u8, champion: &str) {
+    let rule = stats::attack_speed_rule(champion);
+    if rule.attack_damage_per_bonus == 0.0 {
+        return;
+    }
+
+    let bonus = stats::attack_speed(&base_stats(sources), &bonus_stats(sources), level, champion).bonus;
+    let attack_damage = &mut breakdowns[StatKind::AttackDamage as usize];
+    let conversion = Contribution {
+        source: SourceKind::Passive,
+        id: "attackSpeedConversion".to_string(),
+        name: "Bonus attack speed converted to attack damage".to_string(),
+        value: bonus,
+        multiplier: rule.attack_damage_per_bonus / 100.0 * attack_damage.total,
+        amount: bonus * rule.attack_damage_per_bonus / 100.0 * attack_damage.total,
+    };
+    if conversion.amount != 0.0 {
+        attack_damage.total += conversion.amount;
+        attack_damage.contributions.push(conversion);
+    }
+}
+
+/// Attack damage granted by a point of adaptive force.
+const ADAPTIVE_ATTACK_DAMAGE: f64 = 0.6;
+
+/// Adaptive force becomes bonus attack damage if the champion has more bonus attack damage than
+/// ability power, and ability power otherwise. Every adaptive force contribution is added to the
+/// chosen stat under its original source.
+fn apply_adaptive_force(breakdowns: &mut [StatBreakdown]) {
+    let bonus_attack_damage: f64 = breakdowns[StatKind::AttackDamage as usize]
+        .contributions
+        .iter()
+        .filter(|contribution| !matches!(contribution.source, SourceKind::Champion | SourceKind::Level))
+        .map(|contribution| contribution.amount)
+        .sum();
+    let ability_power = breakdowns[StatKind::AbilityPower as usize].total;
+
+    let (kind, multiplier) = if bonus_attack_damage > ability_power {
+        (StatKind::AttackDamage, ADAPTIVE_ATTACK_DAMAGE)
+    } else {
+        (StatKind::AbilityPower, 1.0)
+    };
+
+    let adaptive: Vec<Contribution> = breakdowns[StatKind::AdaptiveForce as usize]
+        .contributions
+        .iter()
+        .map(|contribution| Contribution {
+            value: contribution.amount,
+            multiplier,
+            amount: contribution.amount * multiplier,
+            ..contribution.clone()
+        })
+        .collect();
+
+    let target = &mut breakdowns[kind as usize];
+    target.total += adaptive.iter().map(|contribution| contribution.amount).sum::<f64>();
+    target.contributions.extend(adaptive);
+}
+
+/// Bonus attack speed from every source is scaled by the champion's attack speed ratio, and the
+/// amount over the cap is recorded as its own contribution.
+fn explain_attack_speed(sources: &[Source], level: u8, champion: &str) -> Vec<Contribution> {
+    let rule = stats::attack_speed_rule(champion);
+    let ratio = stats::attack_speed_ratio(&base_stats(sources));
+    let item_multiplier = if rule.item_bonus_applies { ratio / 100.0 } else { 0.0 };
+    let mut contributions = Vec::new();
+
+    for source in sources {
+        let stat = &source.stats[StatKind::AttackSpeed];
+        if source.kind == SourceKind::Champion {
+            contributions.push(Contribution::new(source, SourceKind::Champion, stat.flat, 1.0));
+            contributions.push(Contribution::new(source, SourceKind::Level, stat.per_level, stats::growth(level) * ratio / 100.0));
+        }
+        contributions.push(Contribution::new(source, source.kind, stat.percent, item_multiplier));
+    }
+    contributions.retain(|contribution| contribution.value != 0.0);
+
+    let uncapped: f64 = contributions.iter().map(|contribution| contribution.amount).sum();
+    if uncapped > rule.cap {
+        contributions.push(Contribution {
+            source: SourceKind::Cap,
+            id: "attackSpeedCap".to_string(),
+            name: "Attack speed cap".to_string(),
+            value: rule.cap - uncapped,
+            multiplier: 1.0,
+            amount: rule.cap - uncapped,
+        });
+    }
+
+    contributions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(kind: SourceKind, name: &str, stats: Stats) -> Source {
+        Source {
+            kind,
+            id: name.to_string(),
+            name: name.to_string(),
+            stats,
+        }
+    }
+
+    #[test]
+    fn totals_match_attack_speed_model() {
+        let mut champion = Stats::new();
+        champion[StatKind::AttackSpeed].flat = 0.681;
+        champion[StatKind::AttackSpeed].per_level = 4.0;
+        champion[StatKind::AttackSpeedRatio].flat = 0.568;
+        let mut item = Stats::new();
+        item[StatKind::AttackSpeed].percent = 25.0;
+
+        let sources = [source(SourceKind::Champion, "Caitlyn", champion.clone()), source(SourceKind::Item, "Recurve Bow", item.clone())];
+        let resolved = resolve(&sources, 11, "Caitlyn");
+        let expected = stats::attack_speed(&champion, &item, 11, "Caitlyn").total;
+        assert!((resolved[StatKind::AttackSpeed] - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn percent_scales_summed_total() {
+        let mut champion = Stats::new();
+        champion[StatKind::AbilityPower].flat = 100.0;
+        let mut deathcap = Stats::new();
+        deathcap[StatKind::AbilityPower].flat = 140.0;
+        let mut passive = Stats::new();
+        passive[StatKind::AbilityPower].percent = 30.0;
+
+        let sources = [
+            source(SourceKind::Champion, "Annie", champion),
+            source(SourceKind::Item, "Rabadon's Deathcap", deathcap),
+            source(SourceKind::Passive, "Rabadon's Deathcap", passive),
+        ];
+        let breakdown = explain(&sources, 1, "Annie");
+        let ability_power = &breakdown[StatKind::AbilityPower as usize];
+        assert_eq!(ability_power.contributions.len(), 3);
+        assert_eq!(ability_power.contributions[2].source, SourceKind::Passive);
+        assert!((ability_power.total - 312.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn jhin_converts_bonus_attack_speed_into_attack_damage() {
+        let mut champion = Stats::new();
+        champion[StatKind::AttackDamage].flat = 60.0;
+        champion[StatKind::AttackSpeed].flat = 0.625;
+        let mut item = Stats::new();
+        item[StatKind::AttackDamage].flat = 40.0;
+        item[StatKind::AttackSpeed].percent = 50.0;
+        let sources = [source(SourceKind::Champion, "Jhin", champion), source(SourceKind::Item, "Item", item)];
+
+        // 50% bonus attack speed grants 15% more attack damage
+        let breakdown = explain(&sources, 1, "Jhin");
+        let attack_damage = &breakdown[StatKind::AttackDamage as usize];
+        assert!((attack_damage.total - 115.0).abs() < 1e-9);
+        assert_eq!(attack_damage.contributions.last().unwrap().source, SourceKind::Passive);
+        assert_eq!(breakdown[StatKind::AttackSpeed as usize].total, 0.625);
+
+        let caitlyn = resolve(&sources, 1, "Caitlyn");
+        assert_eq!(caitlyn[StatKind::AttackDamage], 100.0);
+    }
+
+    #[test]
+    fn adaptive_force_follows_the_higher_bonus() {
+        let mut champion = Stats::new();
+        champion[StatKind::AttackDamage].flat = 60.0;
+        let mut rune = Stats::new();
+        rune[StatKind::AdaptiveForce].flat = 10.0;
+        let mut sword = Stats::new();
+        sword[StatKind::AttackDamage].flat = 10.0;
+        let mut tome = Stats::new();
+        tome[StatKind::AbilityPower].flat = 20.0;
+
+        // base attack damage does not count, so ability power wins ties at zero
+        let resolved = resolve(&[source(SourceKind::Champion, "Garen", champion.clone()), source(SourceKind::Bonus, "Rune", rune.clone())], 1, "Garen");
+        assert_eq!(resolved[StatKind::AbilityPower], 10.0);
+        assert_eq!(resolved[StatKind::AttackDamage], 60.0);
+        assert_eq!(resolved[StatKind::AdaptiveForce], 10.0);
+
+        let sources = [
+            source(SourceKind::Champion, "Garen", champion.clone()),
+            source(SourceKind::Bonus, "Rune", rune.clone()),
+            source(SourceKind::Item, "Long Sword", sword),
+        ];
+        let breakdown = explain(&sources, 1, "Garen");
+        let attack_damage = &breakdown[StatKind::AttackDamage as usize];
+        assert!((attack_damage.total - 76.0).abs() < 1e-9);
+        assert_eq!(attack_damage.contributions.last().unwrap().name, "Rune");
+        assert_eq!(breakdown[StatKind::AbilityPower as usize].total, 0.0);
+
+        let resolved = resolve(&[source(SourceKind::Champion, "Garen", champion), source(SourceKind::Bonus, "Rune", rune), source(SourceKind::Item, "Amplifying Tome", tome)], 1, "Garen");
+        assert_eq!(resolved[StatKind::AbilityPower], 30.0);
+        assert_eq!(resolved[StatKind::AttackDamage], 60.0);
+    }
+
+    #[test]
+    fn level_growth_is_its_own_contribution() {
+        let mut champion = Stats::new();
+        champion[StatKind::Health].flat = 690.0;
+        champion[StatKind::Health].per_level = 98.0;
+
+        let breakdown = explain(&[source(SourceKind::Champion, "Garen", champion)], 18, "Garen");
+        let health = &breakdown[StatKind::Health as usize];
+        assert_eq!(health.contributions[1].source, SourceKind::Level);
+        assert!((health.contributions[1].multiplier - 17.0).abs() < 1e-9);
+        assert!((health.total - 2356.0).abs() < 1e-9);
+    }
+}
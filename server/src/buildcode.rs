@@ -0,0 +1,205 @@
+/*
+ * File: buildcode.rs
+ *
+ * Copyright (c) 2025 Alan Fung
+ *
+ * Description: encodes builds as short codes that can be shared as text or in a URL
+ */
+use actix_web::{web, HttpRequest, HttpResponse};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::gamedata::{GameData, SharedGameData};
+use crate::player::{Player, MAX_LEVEL, MIN_LEVEL};
+use crate::session::Sessions;
+use crate::skills;
+
+/// version written at the start of every new code
+const VERSION: u8 = 1;
+/// bytes before the champion name: version, level, skill points and items
+const HEADER_LEN: usize = 2 + 4 + 6 * 2;
+
+/// stores everything a build code describes
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildCode {
+    pub champion: String,
+    pub level: u8,
+    pub skill_points: [u8; 4],
+    pub items: [u16; 6],
+}
+
+impl BuildCode {
+    pub fn from_player(player: &Player) -> Self {
+        BuildCode {
+            champion: player.champ.clone(),
+            level: player.level,
+            skill_points: player.skill_points,
+            items: player.items,
+        }
+    }
+
+    /// Encodes the build as URL safe base64. Version 1 codes are laid out as the version, the
+    /// level, the four skill points, the six item ids in little endian and then the champion name.
+    pub fn encode(&self) -> String {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.champion.len());
+        bytes.push(VERSION);
+        bytes.push(self.level);
+        bytes.extend_from_slice(&self.skill_points);
+        for item in self.items {
+            bytes.extend_from_slice(&item.to_le_bytes());
+        }
+        bytes.extend_from_slice(self.champion.as_bytes());
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    /// Decodes a build code. This only checks that the code is well formed, not that its champion
+    /// and items exist.
+    pub fn decode(code: &str) -> Result<Self, String> {
+        let bytes = URL_SAFE_NO_PAD.decode(code.trim()).map_err(|_| "Build code is not valid base64".to_string())?;
+
+        match bytes.first() {
+            Some(&VERSION) => {}
+            Some(version) => return Err(format!("Unsupported build code version {}", version)),
+            None => return Err("Build code is empty".to_string()),
+        }
+        if bytes.len() <= HEADER_LEN {
+            return Err("Build code is too short".to_string());
+        }
+
+        let level = bytes[1];
+        if !(MIN_LEVEL..=MAX_LEVEL).contains(&level) {
+            return Err(format!("Build code has invalid level {}", level));
+        }
+
+        let mut skill_points = [0; 4];
+        skill_points.copy_from_slice(&bytes[2..6]);
+
+        let mut items = [0; 6];
+        for (i, item) in items.iter_mut().enumerate() {
+            *item = u16::from_le_bytes([bytes[6 + i * 2], bytes[7 + i * 2]]);
+        }
+
+        let champion = String::from_utf8(bytes[HEADER_LEN..].to_vec())
+            .map_err(|_| "Build code has an invalid champion name".to_string())?;
+
+        Ok(BuildCode {
+            champion,
+            level,
+            skill_points,
+            items,
+        })
+    }
+
+    /// Turns the build into a player, rejecting unknown champions and items.
+    pub fn to_player(&self, data: &GameData) -> Result<Player, String> {
+        let base_stats = data.champion(&self.champion)
+            .ok_or_else(|| format!("Unknown champion {}", self.champion))?;
+
+        if let Some(item) = self.items.iter().find(|&&item| item != 0 && data.item(item).is_none()) {
+            return Err(format!("Unknown item {}", item));
+        }
+
+        skills::validate(&self.champion, &self.skill_points, self.level)?;
+
+        let mut player = Player::new();
+        player.champ = self.champion.clone();
+        player.base_stats = base_stats.clone();
+        player.level = self.level;
+        player.skill_points = self.skill_points;
+        player.items = self.items;
+        Ok(player)
+    }
+}
+
+/// This structure is only useful for displaying the output of export_build.
+#[derive(Serialize)]
+struct ExportedBuild {
+    code: String,
+    build: BuildCode,
+}
+
+/// Produces a build code for the session's player.
+pub async fn export_build(req: HttpRequest, sessions: web::Data<Sessions>) -> impl actix_web::Responder {
+    let session = sessions.for_request(&req).await;
+    let session = session.lock().await;
+    let player = &session.player;
+
+    if player.champ == "none" {
+        return HttpResponse::BadRequest().body("Choose a champion before exporting a build");
+    }
+
+    let build = BuildCode::from_player(player);
+    HttpResponse::Ok().json(ExportedBuild { code: build.encode(), build })
+}
+
+/// Replaces the session's player with the build described by a build code.
+pub async fn import_build(req: HttpRequest, sessions: web::Data<Sessions>, game_data: web::Data<SharedGameData>, code: web::Path<String>) -> impl actix_web::Responder {
+    let build = match BuildCode::decode(&code) {
+        Ok(build) => build,
+        Err(err) => return HttpResponse::BadRequest().body(err),
+    };
+
+    let player = match build.to_player(&game_data.get()) {
+        Ok(player) => player,
+        Err(err) => return HttpResponse::BadRequest().body(err),
+    };
+
+    let session = sessions.for_request(&req).await;
+    let mut session = session.lock().await;
+    session.edit("Import build", |current| *current = player);
+    HttpResponse::Ok().json(build)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamedata::tests::sample;
+
+    fn garen() -> BuildCode {
+        BuildCode {
+            champion: "Garen".to_string(),
+            level: 11,
+            skill_points: [5, 2, 3, 1],
+            items: [3071, 3047, 3053, 0, 0, 0],
+        }
+    }
+
+    #[test]
+    fn codes_round_trip() {
+        let code = garen().encode();
+        assert!(code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(BuildCode::decode(&code).unwrap(), garen());
+    }
+
+    #[test]
+    fn players_are_checked_against_the_game_data() {
+        let data = sample();
+        let mut build = BuildCode { items: [3031, 3072, 0, 0, 0, 0], ..garen() };
+        let player = build.to_player(&data).unwrap();
+        assert_eq!(player.base_stats, *data.champion("Garen").unwrap());
+        assert_eq!(player.items, build.items);
+
+        build.items[2] = 9999;
+        assert_eq!(build.to_player(&data).unwrap_err(), "Unknown item 9999");
+        assert_eq!(BuildCode { champion: "Teemo".to_string(), ..garen() }.to_player(&data).unwrap_err(), "Unknown champion Teemo");
+    }
+
+    #[test]
+    fn malformed_codes_are_rejected() {
+        assert!(BuildCode::decode("not base64!").is_err());
+        assert!(BuildCode::decode("").is_err());
+
+        let mut bytes = URL_SAFE_NO_PAD.decode(garen().encode()).unwrap();
+        bytes[0] = 2;
+        assert_eq!(BuildCode::decode(&URL_SAFE_NO_PAD.encode(&bytes)).unwrap_err(), "Unsupported build code version 2");
+
+        bytes[0] = VERSION;
+        bytes[1] = 19;
+        assert!(BuildCode::decode(&URL_SAFE_NO_PAD.encode(&bytes)).is_err());
+
+        bytes.truncate(HEADER_LEN);
+        assert!(BuildCode::decode(&URL_SAFE_NO_PAD.encode(&bytes)).is_err());
+    }
+}
@@ -5,20 +5,21 @@
  *
  * Description: A collection of utility functions dealing with champions
  */
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
 use reqwest::Client;
 use serde_json::Value;
 use std::fs::{self, File};
 use std::io::Write;
-use tokio::sync::Mutex;
 use std::path::Path;
-use crate::stats::{Stat, Stats};
-use crate::player::Player;
+use crate::stats::{Stat, StatKind, Stats};
+use crate::gamedata::SharedGameData;
+use crate::session::Sessions;
+use crate::skills;
 
 
 const CHAMP_URL: &str = "https://cdn.merakianalytics.com/riot/lol/resources/latest/en-US/champions.json";
 const CHAMP_ICON_URL: &str = "https://cdn.communitydragon.org/latest/champion/";
-const CHAMP_CACHE_PATH: &str = "public/champs_cache.json";
+pub const CHAMP_CACHE_PATH: &str = "public/champs_cache.json";
 const CHAMP_ICON_CACHE_PATH: &str = "public/champ_icons";
 
 /// Checks if champion data is cached. If not, creates the cache.
@@ -40,7 +41,7 @@ pub async fn ensure_champ_cache() -> Result<(), String> {
             .await
             .map_err(|_| "Failed to read response body".to_string())?;
 
-        if let Err(_) = fs::create_dir_all(Path::new(CHAMP_CACHE_PATH).parent().unwrap()) {
+        if fs::create_dir_all(Path::new(CHAMP_CACHE_PATH).parent().unwrap()).is_err() {
             return Err("Failed to create cache directory".to_string());
         }
 
@@ -76,7 +77,7 @@ pub async fn update_champ_cache() -> Result<(), String> {
             .await
             .map_err(|_| "Failed to read response body".to_string())?;
 
-        if let Err(_) = fs::create_dir_all(Path::new(CHAMP_CACHE_PATH).parent().unwrap()) {
+        if fs::create_dir_all(Path::new(CHAMP_CACHE_PATH).parent().unwrap()).is_err() {
             return Err("Failed to create cache directory".to_string());
         }
 
@@ -308,36 +309,46 @@ pub async fn get_champion_property_nested(path: web::Path<(String, String)>) ->
 }
 
 /// Updates the player with a given champion.
-pub async fn set_champion(player_data: web::Data<Mutex<Player>>, champion_name: web::Path<String>) -> impl Responder {
+pub async fn set_champion(req: HttpRequest, sessions: web::Data<Sessions>, game_data: web::Data<SharedGameData>, champion_name: web::Path<String>) -> impl Responder {
     let champion_name = champion_name.into_inner();
-
-    let mut player = player_data.lock().await;
-
-    let data = match fs::read_to_string(CHAMP_CACHE_PATH) {
-        Ok(content) => content,
-        Err(_) => return HttpResponse::InternalServerError().body("Failed to read cache file"),
+    let data = game_data.get();
+    let Some(base_stats) = data.champion(&champion_name) else {
+        return HttpResponse::NotFound().body("Champion not found");
     };
 
-    let champs: Value = match serde_json::from_str(&data) {
-        Ok(parsed) => parsed,
-        Err(_) => return HttpResponse::InternalServerError().body("Failed to parse cache file"),
-    };
+    let session = sessions.for_request(&req).await;
+    let mut session = session.lock().await;
+    session.edit(format!("Set champion to {}", champion_name), |player| {
+        player.champ = champion_name.to_string();
+        player.base_stats = base_stats.clone();
 
-    if let Some(champion) = champs.get(&champion_name) {
-        if let Some(base_stats) = champion.get("stats") {
-            player.champ = champion_name.to_string();
-            match map_base_stats(&mut player.base_stats, base_stats) {
-                Ok(()) => {
-                    HttpResponse::Ok().body(format!("Champion {} stats updated successfully!", champion_name))
-                },
-                Err(err) => HttpResponse::InternalServerError().body(format!("Failed to map base stats: {}", err)),
-            }
-        } else {
-            HttpResponse::NotFound().body("Champion base stats not found")
+        // skill points spent under another champion's rules may not be valid anymore
+        if skills::validate(&player.champ, &player.skill_points, player.level).is_err() {
+            player.skill_points = [0; 4];
+            return HttpResponse::Ok().body(format!("Champion {} stats updated successfully! Skill points were reset.", champion_name));
         }
-    } else {
-        HttpResponse::NotFound().body("Champion not found")
-    }
+        HttpResponse::Ok().body(format!("Champion {} stats updated successfully!", champion_name))
+    })
+}
+
+/// Reads a champion's base stats from its entry in the champion data.
+pub fn parse_base_stats(champion: &Value) -> Option<Stats> {
+    let mut stats = Stats::new();
+    map_base_stats(&mut stats, champion.get("stats")?).ok()?;
+    Some(stats)
+}
+
+/// Finds the latest patch any of the given champions was changed in.
+pub fn latest_patch<'a>(champions: impl IntoIterator<Item = &'a Value>) -> Option<String> {
+    champions.into_iter()
+        .filter_map(|champion| champion.get("patchLastChanged").and_then(|v| v.as_str()))
+        .max_by_key(|patch| patch_number(patch))
+        .map(|patch| patch.to_string())
+}
+
+/// Splits a patch such as "14.10" into its numbers so that patches compare correctly.
+fn patch_number(patch: &str) -> Vec<u32> {
+    patch.split('.').map(|part| part.parse().unwrap_or(0)).collect()
 }
 
 /// Helper function to map the player base stats
@@ -352,21 +363,94 @@ fn map_base_stats(stats: &mut Stats, base_stats: &Value) -> Result<(), String> {
         }
     };
 
-    update_stat(&mut stats.armor, "armor");
-    update_stat(&mut stats.attack_damage, "attackDamage");
-    update_stat(&mut stats.attack_speed, "attackSpeed");
-    update_stat(&mut stats.health, "health");
-    update_stat(&mut stats.health_regen, "healthRegen");
-    update_stat(&mut stats.magic_resistance, "magicResistance");
-    update_stat(&mut stats.mana, "mana");
-    update_stat(&mut stats.mana_regen, "manaRegen");
-    update_stat(&mut stats.movespeed, "movespeed");
+    for kind in StatKind::ALL {
+        update_stat(&mut stats[kind], kind.key());
+    }
 
     Ok(())  
 }
 
 /// Fetches the champion associated with the local player.
-pub async fn get_current_champion(player_data: web::Data<Mutex<Player>>) -> impl Responder {
-    let player = player_data.lock().await;
+pub async fn get_current_champion(req: HttpRequest, sessions: web::Data<Sessions>) -> impl Responder {
+    let session = sessions.for_request(&req).await;
+    let session = session.lock().await;
+    let player = &session.player;
     HttpResponse::Ok().body(player.champ.clone())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::breakdown::{self, Source, SourceKind};
+
+    /// Excerpts of the Meraki champion data for the stats with per level growth.
+    const GAREN: &str = r#"{
+        "health": { "flat": 690.0, "percent": 0.0, "perLevel": 98.0, "percentPerLevel": 0.0 },
+        "healthRegen": { "flat": 8.0, "percent": 0.0, "perLevel": 0.5, "percentPerLevel": 0.0 },
+        "armor": { "flat": 38.0, "percent": 0.0, "perLevel": 4.2, "percentPerLevel": 0.0 },
+        "magicResistance": { "flat": 32.0, "percent": 0.0, "perLevel": 2.05, "percentPerLevel": 0.0 },
+        "attackDamage": { "flat": 69.0, "percent": 0.0, "perLevel": 4.5, "percentPerLevel": 0.0 },
+        "movespeed": { "flat": 340.0, "percent": 0.0, "perLevel": 0.0, "percentPerLevel": 0.0 }
+    }"#;
+
+    const ANNIE: &str = r#"{
+        "health": { "flat": 560.0, "percent": 0.0, "perLevel": 96.0, "percentPerLevel": 0.0 },
+        "mana": { "flat": 418.0, "percent": 0.0, "perLevel": 25.0, "percentPerLevel": 0.0 },
+        "manaRegen": { "flat": 8.0, "percent": 0.0, "perLevel": 0.8, "percentPerLevel": 0.0 },
+        "armor": { "flat": 23.0, "percent": 0.0, "perLevel": 4.0, "percentPerLevel": 0.0 },
+        "attackDamage": { "flat": 50.0, "percent": 0.0, "perLevel": 2.65, "percentPerLevel": 0.0 }
+    }"#;
+
+    fn mapped(json: &str) -> Vec<Source> {
+        let mut stats = Stats::new();
+        map_base_stats(&mut stats, &serde_json::from_str(json).unwrap()).unwrap();
+        vec![Source {
+            kind: SourceKind::Champion,
+            id: "champion".to_string(),
+            name: "champion".to_string(),
+            stats,
+        }]
+    }
+
+    /// Checks a stat against reference values at levels 1, 6, 11 and 18.
+    fn assert_levels(sources: &[Source], kind: StatKind, expected: [f64; 4]) {
+        for (level, expected) in [1, 6, 11, 18].into_iter().zip(expected) {
+            let actual = breakdown::resolve(sources, level, "champion")[kind];
+            assert!((actual - expected).abs() < 1e-6, "{} at level {}: expected {}, got {}", kind.key(), level, expected, actual);
+        }
+    }
+
+    #[test]
+    fn garen_reference_values() {
+        let stats = mapped(GAREN);
+        assert_levels(&stats, StatKind::Health, [690.0, 1077.1, 1549.95, 2356.0]);
+        assert_levels(&stats, StatKind::HealthRegen, [8.0, 9.975, 12.3875, 16.5]);
+        assert_levels(&stats, StatKind::Armor, [38.0, 54.59, 74.855, 109.4]);
+        assert_levels(&stats, StatKind::MagicResistance, [32.0, 40.0975, 49.98875, 66.85]);
+        assert_levels(&stats, StatKind::AttackDamage, [69.0, 86.775, 108.4875, 145.5]);
+        assert_levels(&stats, StatKind::Movespeed, [340.0, 340.0, 340.0, 340.0]);
+    }
+
+    #[test]
+    fn annie_reference_values() {
+        let stats = mapped(ANNIE);
+        assert_levels(&stats, StatKind::Health, [560.0, 939.2, 1402.4, 2192.0]);
+        assert_levels(&stats, StatKind::Mana, [418.0, 516.75, 637.375, 843.0]);
+        assert_levels(&stats, StatKind::ManaRegen, [8.0, 11.16, 15.02, 21.6]);
+        assert_levels(&stats, StatKind::Armor, [23.0, 38.8, 58.1, 91.0]);
+        assert_levels(&stats, StatKind::AttackDamage, [50.0, 60.4675, 73.25375, 95.05]);
+    }
+
+    #[test]
+    fn patches_compare_numerically() {
+        assert!(patch_number("14.10") > patch_number("14.9"));
+        assert!(patch_number("15.1") > patch_number("14.24"));
+    }
+
+    #[test]
+    fn growth_is_clamped_to_level_range() {
+        let stats = mapped(GAREN);
+        assert_eq!(breakdown::resolve(&stats, 0, "Garen"), breakdown::resolve(&stats, 1, "Garen"));
+        assert_eq!(breakdown::resolve(&stats, 30, "Garen"), breakdown::resolve(&stats, 18, "Garen"));
+    }
+}
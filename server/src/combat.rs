@@ -0,0 +1,459 @@
+/*
+ * File: combat.rs
+ *
+ * Copyright (c) 2025 Alan Fung
+ *
+ * Description: simulates auto attacks over time, including the on-hit effects of items
+ */
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::breakdown::{self, Source, SourceKind};
+use crate::damage::{mitigation, Dummy, Mitigation, Reductions, Target, TargetStats};
+use crate::evaluate::Build;
+use crate::gamedata::{GameData, SharedGameData};
+use crate::player::critical_strike_damage;
+use crate::session::Sessions;
+use crate::stats::{self, ResolvedStats, StatKind, Stats};
+
+/// attackers with at most this much attack range are melee
+const MELEE_RANGE: f64 = 300.0;
+
+/// what kind of damage an effect deals
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DamageType {
+    Physical,
+    Magic,
+    True,
+}
+
+impl DamageType {
+    /// Returns the portion of this kind of damage that gets through to a target.
+    pub fn multiplier(self, mitigation: &Mitigation) -> f64 {
+        match self {
+            DamageType::Physical => mitigation.physical,
+            DamageType::Magic => mitigation.magic,
+            DamageType::True => mitigation.true_damage,
+        }
+    }
+}
+
+/// stores what an item does when its holder's attacks land
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnHit {
+    pub item: u16,
+    pub name: &'static str,
+    pub damage_type: DamageType,
+    pub flat: f64,
+    /// flat damage gained per level after level 1
+    pub flat_per_level: f64,
+    /// damage per point of a resolved stat
+    pub ratios: &'static [(StatKind, f64)],
+    /// percent of the target's current health dealt by melee and ranged attackers
+    pub current_health: (f64, f64),
+    /// damage is increased by up to this fraction as the target loses health
+    pub missing_health_amp: f64,
+    /// the effect only applies on every nth attack
+    pub every: u32,
+    /// every nth attack applies on-hit effects twice, or 0 for never
+    pub repeat_every: u32,
+    /// bonus attack speed percent gained per attack, up to the maximum stacks
+    pub attack_speed_per_stack: f64,
+    pub max_stacks: u32,
+}
+
+const NO_EFFECT: OnHit = OnHit {
+    item: 0,
+    name: "",
+    damage_type: DamageType::Physical,
+    flat: 0.0,
+    flat_per_level: 0.0,
+    ratios: &[],
+    current_health: (0.0, 0.0),
+    missing_health_amp: 0.0,
+    every: 1,
+    repeat_every: 0,
+    attack_speed_per_stack: 0.0,
+    max_stacks: 0,
+};
+
+/// Every modelled on-hit effect. Adding an item only takes a new entry here.
+pub const ON_HIT: [OnHit; 5] = [
+    OnHit {
+        item: 3153,
+        name: "Blade of the Ruined King",
+        current_health: (9.0, 6.0),
+        ..NO_EFFECT
+    },
+    OnHit {
+        item: 3115,
+        name: "Nashor's Tooth",
+        damage_type: DamageType::Magic,
+        flat: 15.0,
+        ratios: &[(StatKind::AbilityPower, 0.2)],
+        ..NO_EFFECT
+    },
+    OnHit {
+        item: 3091,
+        name: "Wit's End",
+        damage_type: DamageType::Magic,
+        flat: 15.0,
+        flat_per_level: 65.0 / 17.0,
+        ..NO_EFFECT
+    },
+    OnHit {
+        item: 3124,
+        name: "Guinsoo's Rageblade",
+        damage_type: DamageType::Magic,
+        flat: 30.0,
+        repeat_every: 3,
+        attack_speed_per_stack: 8.0,
+        max_stacks: 4,
+        ..NO_EFFECT
+    },
+    OnHit {
+        item: 6672,
+        name: "Kraken Slayer",
+        flat: 150.0,
+        flat_per_level: 50.0 / 17.0,
+        missing_health_amp: 0.75,
+        every: 3,
+        ..NO_EFFECT
+    },
+];
+
+/// Retrieves the on-hit effect of a given item.
+pub fn on_hit(item: u16) -> Option<&'static OnHit> {
+    ON_HIT.iter().find(|effect| effect.item == item)
+}
+
+impl OnHit {
+    /// Computes the damage of one application before resistances, given the target's current
+    /// and maximum health.
+    pub fn raw_damage(&self, attacker: &Attacker, current_health: f64, max_health: f64) -> f64 {
+        let current = current_health.max(0.0);
+        let missing = 1.0 - current / max_health.max(1.0);
+        let percent = if attacker.is_melee() { self.current_health.0 } else { self.current_health.1 };
+        let raw = self.flat
+            + self.flat_per_level * (attacker.level.max(1) - 1) as f64
+            + self.ratios.iter().map(|&(kind, ratio)| attacker.stats[kind] * ratio).sum::<f64>()
+            + percent / 100.0 * current;
+        raw * (1.0 + self.missing_health_amp * missing)
+    }
+}
+
+/// seconds before spellblade can empower another attack
+pub const SPELLBLADE_COOLDOWN: f64 = 1.5;
+
+/// stores the damage an item adds to the first attack after an ability is cast
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spellblade {
+    pub item: u16,
+    pub name: &'static str,
+    pub damage_type: DamageType,
+    /// damage per point of base attack damage
+    pub base_attack_damage: f64,
+    /// damage per point of bonus attack damage
+    pub bonus_attack_damage: f64,
+    /// damage per point of ability power
+    pub ability_power: f64,
+}
+
+/// Every modelled spellblade item. The items share a unique passive, so a build has at most one.
+pub const SPELLBLADE: [Spellblade; 5] = [
+    Spellblade { item: 3057, name: "Sheen", damage_type: DamageType::Physical, base_attack_damage: 1.0, bonus_attack_damage: 0.0, ability_power: 0.0 },
+    Spellblade { item: 3078, name: "Trinity Force", damage_type: DamageType::Physical, base_attack_damage: 2.0, bonus_attack_damage: 0.0, ability_power: 0.0 },
+    Spellblade { item: 3100, name: "Lich Bane", damage_type: DamageType::Magic, base_attack_damage: 0.75, bonus_attack_damage: 0.0, ability_power: 0.45 },
+    Spellblade { item: 6662, name: "Iceborn Gauntlet", damage_type: DamageType::Physical, base_attack_damage: 1.5, bonus_attack_damage: 0.0, ability_power: 0.0 },
+    Spellblade { item: 3508, name: "Essence Reaver", damage_type: DamageType::Physical, base_attack_damage: 1.0, bonus_attack_damage: 0.4, ability_power: 0.0 },
+];
+
+/// Finds the spellblade effect among a set of items, if any.
+pub fn spellblade(items: &[u16]) -> Option<&'static Spellblade> {
+    items.iter().find_map(|&item| SPELLBLADE.iter().find(|effect| effect.item == item))
+}
+
+impl Spellblade {
+    /// Computes the bonus damage of an empowered attack before resistances.
+    pub fn raw_damage(&self, attacker: &Attacker) -> f64 {
+        let base_attack_damage = attacker.base_value(StatKind::AttackDamage);
+        let bonus_attack_damage = attacker.stats[StatKind::AttackDamage] - base_attack_damage;
+        self.base_attack_damage * base_attack_damage
+            + self.bonus_attack_damage * bonus_attack_damage
+            + self.ability_power * attacker.stats[StatKind::AbilityPower]
+    }
+}
+
+/// stores everything about an attacker that matters for its auto attacks
+#[derive(Debug, Clone)]
+pub struct Attacker {
+    pub champion: String,
+    pub level: u8,
+    /// base stats from the champion, used to recompute attack speed as it stacks
+    pub base: Stats,
+    pub bonus: Stats,
+    pub stats: ResolvedStats,
+    pub items: Vec<u16>,
+}
+
+impl Attacker {
+    /// Gathers an attacker from its stat sources.
+    pub fn from_sources(sources: &[Source], level: u8, champion: &str) -> Self {
+        Attacker {
+            champion: champion.to_string(),
+            level,
+            base: breakdown::base_stats(sources),
+            bonus: breakdown::bonus_stats(sources),
+            stats: breakdown::resolve(sources, level, champion),
+            items: sources.iter()
+                .filter(|source| source.kind == SourceKind::Item)
+                .filter_map(|source| source.id.parse().ok())
+                .collect(),
+        }
+    }
+
+    /// Computes attacks per second with some extra bonus attack speed.
+    pub fn attacks_per_second(&self, extra_percent: f64) -> f64 {
+        let mut bonus = self.bonus.clone();
+        bonus[StatKind::AttackSpeed].percent += extra_percent;
+        stats::attack_speed(&self.base, &bonus, self.level, &self.champion).total
+    }
+
+    /// Returns a stat from the champion alone at the attacker's level.
+    pub fn base_value(&self, kind: StatKind) -> f64 {
+        let stat = &self.base[kind];
+        stat.flat + stat.per_level * stats::growth(self.level)
+    }
+
+    /// Computes the damage of an auto attack before resistances, averaging in critical strikes.
+    pub fn attack_damage(&self) -> f64 {
+        let crit_chance = self.stats[StatKind::CriticalStrikeChance].clamp(0.0, 100.0) / 100.0;
+        let crit_damage = critical_strike_damage(&self.stats) / 100.0;
+        self.stats[StatKind::AttackDamage] * (1.0 + crit_chance * (crit_damage - 1.0).max(0.0))
+    }
+
+    pub fn is_melee(&self) -> bool {
+        self.stats[StatKind::AttackRange] <= MELEE_RANGE
+    }
+}
+
+/// stores damage dealt by one source over a simulation
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceDamage {
+    pub source: String,
+    pub damage: f64,
+}
+
+/// stores the result of attacking a target for a while
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DpsResult {
+    pub dps: f64,
+    pub damage: f64,
+    pub attacks: u32,
+    /// seconds covered by the attacks made
+    pub duration: f64,
+    /// seconds until the attack that takes the target's health to zero, if any
+    pub time_to_kill: Option<f64>,
+    pub sources: Vec<SourceDamage>,
+    pub mitigation: Mitigation,
+}
+
+/// Simulates auto attacks against a target for a number of seconds. Critical strikes are
+/// averaged into every attack, so results do not depend on luck. The target keeps taking damage
+/// after dying, so effects based on current health stop adding damage at that point.
+pub fn simulate(attacker: &Attacker, target: &TargetStats, reductions: &Reductions, duration: f64) -> DpsResult {
+    let mitigation = mitigation(&attacker.stats, target, reductions);
+    let attack = attacker.attack_damage() * mitigation.physical;
+
+    let effects: Vec<&OnHit> = attacker.items.iter().filter_map(|&item| on_hit(item)).collect();
+    let mut sources = vec![SourceDamage { source: "Attacks".to_string(), damage: 0.0 }];
+    sources.extend(effects.iter().map(|effect| SourceDamage { source: effect.name.to_string(), damage: 0.0 }));
+
+    let mut health = target.health;
+    let mut elapsed = 0.0;
+    let mut attacks = 0;
+    let mut time_to_kill = None;
+
+    while elapsed < duration {
+        attacks += 1;
+        let stacks = attacks - 1;
+        let extra_attack_speed: f64 = effects.iter()
+            .map(|effect| effect.attack_speed_per_stack * stacks.min(effect.max_stacks) as f64)
+            .sum();
+
+        let mut dealt = attack;
+        sources[0].damage += attack;
+
+        let repeated = effects.iter().any(|effect| effect.repeat_every != 0 && attacks % effect.repeat_every == 0);
+        for (i, effect) in effects.iter().enumerate() {
+            if attacks % effect.every != 0 {
+                continue;
+            }
+
+            let applications = if repeated && effect.every == 1 { 2.0 } else { 1.0 };
+            let damage = effect.raw_damage(attacker, health, target.health) * effect.damage_type.multiplier(&mitigation) * applications;
+
+            dealt += damage;
+            sources[i + 1].damage += damage;
+        }
+
+        if health > 0.0 && health - dealt <= 0.0 {
+            time_to_kill = Some(elapsed);
+        }
+        health -= dealt;
+        elapsed += 1.0 / attacker.attacks_per_second(extra_attack_speed);
+    }
+
+    let damage = sources.iter().map(|source| source.damage).sum();
+    DpsResult {
+        dps: if elapsed > 0.0 { damage / elapsed } else { 0.0 },
+        damage,
+        attacks,
+        duration: elapsed,
+        time_to_kill,
+        sources,
+        mitigation,
+    }
+}
+
+/// This structure is only useful for reading the body of player_dps.
+#[derive(Deserialize)]
+pub struct DpsRequest {
+    /// target attacked, which defaults to a dummy without resistances
+    #[serde(default)]
+    target: Option<Target>,
+    #[serde(default)]
+    reductions: Reductions,
+    /// seconds to attack for
+    #[serde(default = "default_duration")]
+    duration: f64,
+}
+
+fn default_duration() -> f64 {
+    10.0
+}
+
+impl DpsRequest {
+    /// Resolves the target and checks the duration.
+    fn resolve(&self, data: &GameData) -> Result<TargetStats, String> {
+        if !(self.duration > 0.0 && self.duration <= 300.0) {
+            return Err("Duration must be between 0 and 300 seconds".to_string());
+        }
+        self.target.clone().unwrap_or_else(|| Target::Dummy(Dummy::default())).resolve(data)
+    }
+}
+
+/// Simulates the player's auto attacks against a target.
+pub async fn player_dps(req: HttpRequest, sessions: web::Data<Sessions>, game_data: web::Data<SharedGameData>, body: web::Json<DpsRequest>) -> impl actix_web::Responder {
+    let request = body.into_inner();
+    let data = game_data.get();
+    let target = match request.resolve(&data) {
+        Ok(target) => target,
+        Err(err) => return HttpResponse::BadRequest().body(err),
+    };
+
+    let session = sessions.for_request(&req).await;
+    let session = session.lock().await;
+    let player = &session.player;
+    let attacker = Attacker::from_sources(&player.sources(&data), player.level, &player.champ);
+
+    HttpResponse::Ok().json(simulate(&attacker, &target, &request.reductions, request.duration))
+}
+
+/// This structure is only useful for reading the body of build_dps.
+#[derive(Deserialize)]
+pub struct BuildDpsRequest {
+    #[serde(flatten)]
+    build: Build,
+    #[serde(flatten)]
+    dps: DpsRequest,
+}
+
+/// Simulates the auto attacks of the build described in the body against a target.
+pub async fn build_dps(game_data: web::Data<SharedGameData>, body: web::Json<BuildDpsRequest>) -> impl actix_web::Responder {
+    let request = body.into_inner();
+    let data = game_data.get();
+    let target = match request.dps.resolve(&data) {
+        Ok(target) => target,
+        Err(err) => return HttpResponse::BadRequest().body(err),
+    };
+
+    match request.build.sources(&data) {
+        Ok((sources, _)) => {
+            let attacker = Attacker::from_sources(&sources, request.build.level, &request.build.champion);
+            HttpResponse::Ok().json(simulate(&attacker, &target, &request.dps.reductions, request.dps.duration))
+        }
+        Err(err) => HttpResponse::BadRequest().body(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamedata::tests::sample;
+
+    const DUMMY: TargetStats = TargetStats { armor: 0.0, magic_resistance: 0.0, health: 1000.0 };
+
+    fn attacker(champion: &str, level: u8, items: &[u16]) -> Attacker {
+        let build = Build { champion: champion.to_string(), level, items: items.to_vec(), skill_points: [0; 4] };
+        let (sources, _) = build.sources(&sample()).unwrap();
+        Attacker::from_sources(&sources, level, champion)
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn plain_attacks_match_attack_damage_times_attack_speed() {
+        let garen = attacker("Garen", 1, &[]);
+        let result = simulate(&garen, &TargetStats { health: 1e9, ..DUMMY }, &Reductions::default(), 10.0);
+        assert!(close(result.dps, 69.0 * 0.625));
+        assert_eq!(result.attacks, 7);
+        assert_eq!(result.time_to_kill, None);
+        assert_eq!(result.sources.len(), 1);
+    }
+
+    #[test]
+    fn crits_are_averaged_and_armor_applies() {
+        let mut garen = attacker("Garen", 1, &[]);
+        garen.stats[StatKind::CriticalStrikeChance] = 50.0;
+        let target = TargetStats { armor: 100.0, health: 1e9, ..DUMMY };
+        let result = simulate(&garen, &target, &Reductions::default(), 10.0);
+        assert!(close(result.dps, 69.0 * 1.375 * 0.5 * 0.625));
+    }
+
+    #[test]
+    fn on_hit_effects() {
+        let target = TargetStats { health: 1e9, ..DUMMY };
+
+        // Nashor's Tooth itself grants 80 ability power
+        let nashor = simulate(&attacker("Annie", 1, &[3115]), &target, &Reductions::default(), 5.0);
+        assert!(close(nashor.sources[1].damage, (15.0 + 0.2 * 80.0) * nashor.attacks as f64));
+
+        let kraken = simulate(&attacker("Garen", 18, &[6672]), &TargetStats { health: 1e15, ..DUMMY }, &Reductions::default(), 10.0);
+        assert!(close(kraken.sources[1].damage, 200.0 * (kraken.attacks / 3) as f64));
+
+        let botrk = simulate(&attacker("Garen", 1, &[3153]), &TargetStats { health: 1000.0, ..DUMMY }, &Reductions::default(), 1.0);
+        assert!(close(botrk.sources[1].damage, 90.0));
+    }
+
+    #[test]
+    fn guinsoo_stacks_and_repeats_on_hits() {
+        let target = TargetStats { health: 1e9, ..DUMMY };
+        let result = simulate(&attacker("Garen", 1, &[3124, 3115]), &target, &Reductions::default(), 10.0);
+        let repeats = (result.attacks / 3) as f64;
+        assert!(close(result.sources[1].damage, 30.0 * (result.attacks as f64 + repeats)));
+        assert!(close(result.sources[2].damage, (15.0 + 0.2 * 110.0) * (result.attacks as f64 + repeats)));
+        assert!(result.attacks > 7);
+    }
+
+    #[test]
+    fn targets_can_die() {
+        let result = simulate(&attacker("Garen", 1, &[]), &TargetStats { health: 200.0, ..DUMMY }, &Reductions::default(), 10.0);
+        assert!(close(result.time_to_kill.unwrap(), 2.0 / 0.625));
+    }
+}
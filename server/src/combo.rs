@@ -0,0 +1,379 @@
+/*
+ * File: combo.rs
+ *
+ * Copyright (c) 2025 Alan Fung
+ *
+ * Description: adds up the damage of a sequence of abilities and auto attacks
+ */
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::ability::Ability;
+use crate::combat::{self, Attacker, DamageType, SPELLBLADE_COOLDOWN};
+use crate::damage::{mitigation, Dummy, Mitigation, Reductions, Target, TargetStats};
+use crate::evaluate::Build;
+use crate::gamedata::{GameData, SharedGameData};
+use crate::session::Sessions;
+use crate::skills::ABILITY_NAMES;
+
+/// most steps a combo can have
+const MAX_STEPS: usize = 50;
+
+/// one step of a combo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Q, W, E or R by its index
+    Ability(usize),
+    Attack,
+}
+
+impl Step {
+    fn label(self) -> &'static str {
+        match self {
+            Step::Ability(ability) => ABILITY_NAMES[ability],
+            Step::Attack => "AA",
+        }
+    }
+}
+
+/// Reads a combo such as "Q-AA-E-W-R-AA". Steps can be separated by dashes, commas, spaces or
+/// arrows, and are not case sensitive.
+pub fn parse_combo(combo: &str) -> Result<Vec<Step>, String> {
+    let steps = combo.split(|c: char| c == '-' || c == ',' || c == '>' || c.is_whitespace())
+        .filter(|step| !step.is_empty())
+        .map(|step| match step.to_ascii_uppercase().as_str() {
+            "AA" | "A" => Ok(Step::Attack),
+            name => ABILITY_NAMES.iter()
+                .position(|&ability| ability == name)
+                .map(Step::Ability)
+                .ok_or_else(|| format!("Unknown combo step {}", step)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if steps.is_empty() {
+        return Err("A combo needs at least one step".to_string());
+    }
+    if steps.len() > MAX_STEPS {
+        return Err(format!("A combo can have at most {} steps", MAX_STEPS));
+    }
+    Ok(steps)
+}
+
+/// stores one instance of damage dealt during a step
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Hit {
+    /// the ability, attack or item that dealt the damage
+    pub source: String,
+    pub damage_type: DamageType,
+    /// damage after resistances
+    pub damage: f64,
+}
+
+/// stores the damage of one step of a combo
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StepResult {
+    pub step: String,
+    pub name: String,
+    pub hits: Vec<Hit>,
+    pub damage: f64,
+    /// target health left after the step, which can go below zero
+    pub target_health: f64,
+    pub target_health_percent: f64,
+}
+
+/// stores the damage of a whole combo
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ComboResult {
+    pub damage: f64,
+    pub steps: Vec<StepResult>,
+    pub target_health: f64,
+    pub target_health_percent: f64,
+    /// index of the step that takes the target's health to zero, if any
+    pub kill_step: Option<usize>,
+    pub mitigation: Mitigation,
+}
+
+/// Adds up the damage of a combo against a target. Abilities are cast instantly and their
+/// cooldowns are ignored, while each attack takes one attack interval. Casting an ability
+/// empowers the next attack with the build's spellblade item unless the spellblade is still on
+/// cooldown from its last proc, and attacks apply on-hit effects with critical strikes averaged
+/// in.
+pub fn combo(attacker: &Attacker, abilities: &[Option<Ability>; 4], skill_points: &[u8; 4], steps: &[Step], target: &TargetStats, reductions: &Reductions) -> Result<ComboResult, String> {
+    let mitigation = mitigation(&attacker.stats, target, reductions);
+    let spellblade = combat::spellblade(&attacker.items);
+    let effects: Vec<_> = attacker.items.iter().filter_map(|&item| combat::on_hit(item)).collect();
+
+    let attacks_per_second = attacker.attacks_per_second(0.0);
+    let attack_interval = if attacks_per_second > 0.0 { 1.0 / attacks_per_second } else { 0.0 };
+
+    let mut health = target.health;
+    let mut time = 0.0;
+    let mut attacks = 0;
+    let mut empowered = false;
+    let mut spellblade_ready_at = 0.0;
+    let mut kill_step = None;
+    let mut results = Vec::new();
+
+    for (i, &step) in steps.iter().enumerate() {
+        let mut hits = Vec::new();
+        let hit = |source: &str, damage_type: DamageType, raw: f64| Hit {
+            source: source.to_string(),
+            damage_type,
+            damage: raw * damage_type.multiplier(&mitigation),
+        };
+
+        let name = match step {
+            Step::Ability(index) => {
+                let ability = abilities[index].as_ref()
+                    .ok_or_else(|| format!("{} has no data for {}", attacker.champion, ABILITY_NAMES[index]))?;
+                let rank = skill_points[index];
+                if rank == 0 {
+                    return Err(format!("{} has not been ranked up", ABILITY_NAMES[index]));
+                }
+
+                if !ability.damage.is_empty() {
+                    hits.push(hit(&ability.name, ability.damage_type, ability.raw_damage(attacker, rank, health, target.health)));
+                }
+                if time >= spellblade_ready_at {
+                    empowered = spellblade.is_some();
+                }
+                ability.name.clone()
+            }
+            Step::Attack => {
+                attacks += 1;
+                hits.push(hit("Attack", DamageType::Physical, attacker.attack_damage()));
+
+                if let Some(spellblade) = spellblade.filter(|_| empowered) {
+                    hits.push(hit(spellblade.name, spellblade.damage_type, spellblade.raw_damage(attacker)));
+                    empowered = false;
+                    spellblade_ready_at = time + SPELLBLADE_COOLDOWN;
+                }
+
+                let repeated = effects.iter().any(|effect| effect.repeat_every != 0 && attacks % effect.repeat_every == 0);
+                for effect in effects.iter().filter(|effect| attacks % effect.every == 0) {
+                    let applications = if repeated && effect.every == 1 { 2.0 } else { 1.0 };
+                    hits.push(hit(effect.name, effect.damage_type, effect.raw_damage(attacker, health, target.health) * applications));
+                }
+                time += attack_interval;
+                "Auto attack".to_string()
+            }
+        };
+
+        let damage: f64 = hits.iter().map(|hit| hit.damage).sum();
+        if health > 0.0 && health - damage <= 0.0 {
+            kill_step = Some(i);
+        }
+        health -= damage;
+
+        results.push(StepResult {
+            step: step.label().to_string(),
+            name,
+            hits,
+            damage,
+            target_health: health,
+            target_health_percent: health_percent(health, target.health),
+        });
+    }
+
+    Ok(ComboResult {
+        damage: results.iter().map(|step| step.damage).sum(),
+        steps: results,
+        target_health: health,
+        target_health_percent: health_percent(health, target.health),
+        kill_step,
+        mitigation,
+    })
+}
+
+/// Turns health into a percent of maximum health, stopping at zero.
+fn health_percent(health: f64, max_health: f64) -> f64 {
+    if max_health <= 0.0 { 0.0 } else { health.max(0.0) / max_health * 100.0 }
+}
+
+/// Adds up the damage of a full combo from a build: every ranked ability with damage in order,
+/// followed by an auto attack.
+pub fn full_combo(data: &GameData, build: &Build, target: &TargetStats) -> Result<ComboResult, String> {
+    let (sources, _) = build.sources(data)?;
+    let abilities = abilities(data, &build.champion)?;
+
+    let mut steps: Vec<Step> = (0..4)
+        .filter(|&index| build.skill_points[index] > 0)
+        .filter(|&index| abilities[index].as_ref().is_some_and(|ability| !ability.damage.is_empty()))
+        .map(Step::Ability)
+        .collect();
+    steps.push(Step::Attack);
+
+    let attacker = Attacker::from_sources(&sources, build.level, &build.champion);
+    combo(&attacker, abilities, &build.skill_points, &steps, target, &Reductions::default())
+}
+
+/// Looks up a champion's abilities in the game data.
+fn abilities<'a>(data: &'a GameData, champion: &str) -> Result<&'a [Option<Ability>; 4], String> {
+    data.abilities(champion).ok_or_else(|| format!("No ability data for {}", champion))
+}
+
+/// This structure is only useful for reading the body of player_combo.
+#[derive(Deserialize)]
+pub struct ComboRequest {
+    /// steps such as "Q-AA-E-W-R-AA"
+    combo: String,
+    /// target hit by the combo, which defaults to a dummy without resistances
+    #[serde(default)]
+    target: Option<Target>,
+    #[serde(default)]
+    reductions: Reductions,
+}
+
+impl ComboRequest {
+    /// Reads the combo and resolves the target.
+    fn resolve(&self, data: &GameData) -> Result<(Vec<Step>, TargetStats), String> {
+        let steps = parse_combo(&self.combo)?;
+        let target = self.target.clone().unwrap_or_else(|| Target::Dummy(Dummy::default())).resolve(data)?;
+        Ok((steps, target))
+    }
+}
+
+/// Adds up the damage of a combo cast by the player.
+pub async fn player_combo(req: HttpRequest, sessions: web::Data<Sessions>, game_data: web::Data<SharedGameData>, body: web::Json<ComboRequest>) -> impl actix_web::Responder {
+    let request = body.into_inner();
+    let data = game_data.get();
+    let (steps, target) = match request.resolve(&data) {
+        Ok(resolved) => resolved,
+        Err(err) => return HttpResponse::BadRequest().body(err),
+    };
+
+    let session = sessions.for_request(&req).await;
+    let session = session.lock().await;
+    let player = &session.player;
+
+    let attacker = Attacker::from_sources(&player.sources(&data), player.level, &player.champ);
+    let result = abilities(&data, &player.champ)
+        .and_then(|abilities| combo(&attacker, abilities, &player.skill_points, &steps, &target, &request.reductions));
+
+    match result {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(err) => HttpResponse::BadRequest().body(err),
+    }
+}
+
+/// This structure is only useful for reading the body of build_combo.
+#[derive(Deserialize)]
+pub struct BuildComboRequest {
+    #[serde(flatten)]
+    build: Build,
+    #[serde(flatten)]
+    combo: ComboRequest,
+}
+
+/// Adds up the damage of a combo cast by the build described in the body.
+pub async fn build_combo(game_data: web::Data<SharedGameData>, body: web::Json<BuildComboRequest>) -> impl actix_web::Responder {
+    let request = body.into_inner();
+    let data = game_data.get();
+    let (steps, target) = match request.combo.resolve(&data) {
+        Ok(resolved) => resolved,
+        Err(err) => return HttpResponse::BadRequest().body(err),
+    };
+
+    let build = &request.build;
+    let result = build.sources(&data).and_then(|(sources, _)| {
+        let attacker = Attacker::from_sources(&sources, build.level, &build.champion);
+        combo(&attacker, abilities(&data, &build.champion)?, &build.skill_points, &steps, &target, &request.combo.reductions)
+    });
+
+    match result {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(err) => HttpResponse::BadRequest().body(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamedata::tests::sample;
+    use crate::stats::StatKind;
+
+    const DUMMY: TargetStats = TargetStats { armor: 0.0, magic_resistance: 0.0, health: 1000.0 };
+
+    fn run(champion: &str, level: u8, items: &[u16], skill_points: [u8; 4], text: &str, target: &TargetStats) -> Result<ComboResult, String> {
+        let data = sample();
+        let build = Build { champion: champion.to_string(), level, items: items.to_vec(), skill_points };
+        let (sources, _) = build.sources(&data).unwrap();
+        let attacker = Attacker::from_sources(&sources, level, champion);
+        combo(&attacker, data.abilities(champion).unwrap(), &skill_points, &parse_combo(text)?, target, &Reductions::default())
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn combos_are_parsed() {
+        let steps = parse_combo("Q-AA-E-W-R-AA").unwrap();
+        assert_eq!(steps, [Step::Ability(0), Step::Attack, Step::Ability(2), Step::Ability(1), Step::Ability(3), Step::Attack]);
+        assert_eq!(parse_combo("q, aa > r").unwrap().len(), 3);
+        assert!(parse_combo("Q-X").is_err());
+        assert!(parse_combo(" - ").is_err());
+    }
+
+    #[test]
+    fn abilities_scale_with_stats() {
+        let result = run("Annie", 1, &[], [1, 0, 0, 0], "Q", &TargetStats { magic_resistance: 100.0, ..DUMMY }).unwrap();
+        assert!(close(result.damage, 80.0 * 0.5));
+        assert_eq!(result.steps[0].name, "Disintegrate");
+        assert_eq!(result.steps[0].hits[0].damage_type, DamageType::Magic);
+        assert!(close(result.target_health_percent, 96.0));
+    }
+
+    #[test]
+    fn spellblade_empowers_the_next_attack_only() {
+        let result = run("Garen", 1, &[3057], [1, 0, 0, 0], "Q-AA-AA", &DUMMY).unwrap();
+        assert!(close(result.steps[0].damage, 30.0 + 0.5 * 69.0));
+        assert_eq!(result.steps[1].hits[1].source, "Sheen");
+        assert!(close(result.steps[1].damage, 2.0 * 69.0));
+        assert!(close(result.steps[2].damage, 69.0));
+    }
+
+    #[test]
+    fn spellblade_waits_for_its_cooldown() {
+        let sheen = |result: &ComboResult, step: usize| result.steps[step].hits.iter().any(|hit| hit.source == "Sheen");
+
+        // a level 18 attack takes less than the cooldown, so W is cast while Sheen is recharging
+        let fast = run("Garen", 18, &[3057], [5, 5, 5, 3], "Q-AA-W-AA", &DUMMY).unwrap();
+        assert!(sheen(&fast, 1));
+        assert!(!sheen(&fast, 3));
+
+        // a level 1 attack takes longer than the cooldown, so Sheen is ready again
+        let slow = run("Garen", 1, &[3057], [1, 0, 0, 0], "Q-AA-Q-AA", &DUMMY).unwrap();
+        assert!(sheen(&slow, 1));
+        assert!(sheen(&slow, 3));
+    }
+
+    #[test]
+    fn executes_use_missing_health_and_kills_are_found() {
+        let result = run("Garen", 6, &[], [1, 0, 0, 1], "AA-R", &TargetStats { health: 250.0, ..DUMMY }).unwrap();
+        let attack = result.steps[0].damage;
+        assert!(close(result.steps[1].damage, 150.0 + 0.25 * attack));
+        assert_eq!(result.steps[1].hits[0].damage_type, DamageType::True);
+        assert_eq!(result.kill_step, Some(1));
+        assert_eq!(result.target_health_percent, 0.0);
+    }
+
+    #[test]
+    fn on_hit_effects_apply_to_attacks() {
+        let result = run("Annie", 1, &[3115], [1, 0, 0, 0], "AA-Q-AA", &DUMMY).unwrap();
+        let nashor = |step: usize| result.steps[step].hits.iter().find(|hit| hit.source == "Nashor's Tooth").unwrap().damage;
+        assert!(close(nashor(0), 15.0 + 0.2 * 80.0));
+        assert!(close(nashor(2), 15.0 + 0.2 * 80.0));
+    }
+
+    #[test]
+    fn unranked_abilities_are_errors() {
+        assert_eq!(run("Garen", 1, &[], [1, 0, 0, 0], "W", &DUMMY).unwrap_err(), "W has not been ranked up");
+        assert!(run("Annie", 3, &[], [1, 1, 1, 0], "E", &DUMMY).unwrap_err().contains("no data"));
+        let garen = run("Garen", 1, &[], [1, 0, 0, 0], "AA", &DUMMY).unwrap();
+        assert!(close(garen.damage, sample().champion("Garen").unwrap()[StatKind::AttackDamage].flat));
+    }
+}
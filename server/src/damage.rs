@@ -0,0 +1,376 @@
+/*
+ * File: damage.rs
+ *
+ * Copyright (c) 2025 Alan Fung
+ *
+ * Description: targets and the mitigation of damage dealt to them
+ */
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::breakdown::{self, Source, SourceKind};
+use crate::gamedata::{GameData, SharedGameData};
+use crate::player::{MAX_LEVEL, MIN_LEVEL};
+use crate::session::Sessions;
+use crate::stats::{ResolvedStats, StatKind};
+
+/// a target with fixed resistances, like the practice tool dummy
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct Dummy {
+    pub armor: f64,
+    pub magic_resistance: f64,
+    pub health: f64,
+}
+
+impl Default for Dummy {
+    fn default() -> Self {
+        Dummy {
+            armor: 0.0,
+            magic_resistance: 0.0,
+            health: 1000.0,
+        }
+    }
+}
+
+/// an enemy champion with a level and items
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Enemy {
+    pub champion: String,
+    pub level: u8,
+    #[serde(default)]
+    pub items: Vec<u16>,
+}
+
+/// describes who damage is dealt to: an enemy when a champion is given, otherwise a dummy
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Target {
+    Enemy(Enemy),
+    Dummy(Dummy),
+}
+
+/// stores the target stats that matter for mitigation
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetStats {
+    pub armor: f64,
+    pub magic_resistance: f64,
+    pub health: f64,
+}
+
+/// resistance reductions applied to the target, such as Black Cleaver's armor shred
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Reductions {
+    pub armor_flat: f64,
+    pub armor_percent: f64,
+    pub magic_resistance_flat: f64,
+    pub magic_resistance_percent: f64,
+}
+
+/// stores how much of each damage type gets through to the target
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Mitigation {
+    pub target: TargetStats,
+    pub effective_armor: f64,
+    pub effective_magic_resistance: f64,
+    pub physical: f64,
+    pub magic: f64,
+    #[serde(rename = "true")]
+    pub true_damage: f64,
+}
+
+impl Enemy {
+    /// Resolves all of the enemy's stats at its level with its items. Levels outside the game's
+    /// range and unknown items are errors.
+    pub fn resolved_stats(&self, data: &GameData) -> Result<ResolvedStats, String> {
+        if !(MIN_LEVEL..=MAX_LEVEL).contains(&self.level) {
+            return Err(format!("Level must be between {} and {}", MIN_LEVEL, MAX_LEVEL));
+        }
+        let base_stats = data.champion(&self.champion)
+            .ok_or_else(|| format!("Champion {} not found", self.champion))?;
+
+        let mut sources = vec![Source {
+            kind: SourceKind::Champion,
+            id: self.champion.clone(),
+            name: self.champion.clone(),
+            stats: base_stats.clone(),
+        }];
+        sources.extend(data.item_sources(&self.items)?);
+
+        Ok(breakdown::resolve(&sources, self.level, &self.champion))
+    }
+}
+
+impl TargetStats {
+    /// Picks out the target stats from resolved stats.
+    pub fn from_resolved(resolved: &ResolvedStats) -> Self {
+        TargetStats {
+            armor: resolved[StatKind::Armor],
+            magic_resistance: resolved[StatKind::MagicResistance],
+            health: resolved[StatKind::Health],
+        }
+    }
+}
+
+impl Target {
+    /// Resolves the target's armor, magic resistance and health. Enemy champions are resolved
+    /// at their level with their items.
+    pub fn resolve(&self, data: &GameData) -> Result<TargetStats, String> {
+        match self {
+            Target::Dummy(dummy) => Ok(TargetStats {
+                armor: dummy.armor,
+                magic_resistance: dummy.magic_resistance,
+                health: dummy.health,
+            }),
+            Target::Enemy(enemy) => Ok(TargetStats::from_resolved(&enemy.resolved_stats(data)?)),
+        }
+    }
+}
+
+/// Applies reductions and penetration to a resistance in the game's order: flat reduction,
+/// percent reduction, percent penetration, then flat penetration. Reductions can take a
+/// resistance below zero, but penetration cannot.
+pub fn effective_resistance(resistance: f64, flat_reduction: f64, percent_reduction: f64, percent_penetration: f64, flat_penetration: f64) -> f64 {
+    let resistance = resistance - flat_reduction;
+    if resistance <= 0.0 {
+        return resistance;
+    }
+
+    let resistance = resistance * (1.0 - percent_reduction / 100.0);
+    let resistance = resistance * (1.0 - percent_penetration / 100.0);
+    (resistance - flat_penetration).max(0.0)
+}
+
+/// Returns the portion of damage dealt through a given resistance. Negative resistance amplifies
+/// damage.
+pub fn damage_multiplier(resistance: f64) -> f64 {
+    if resistance >= 0.0 {
+        100.0 / (100.0 + resistance)
+    } else {
+        2.0 - 100.0 / (100.0 - resistance)
+    }
+}
+
+/// Computes the damage multipliers of an attacker's resolved stats against a target.
+pub fn mitigation(attacker: &ResolvedStats, target: &TargetStats, reductions: &Reductions) -> Mitigation {
+    let effective_armor = effective_resistance(
+        target.armor,
+        reductions.armor_flat,
+        reductions.armor_percent,
+        attacker[StatKind::ArmorPenetration],
+        attacker[StatKind::Lethality],
+    );
+    let effective_magic_resistance = effective_resistance(
+        target.magic_resistance,
+        reductions.magic_resistance_flat,
+        reductions.magic_resistance_percent,
+        attacker[StatKind::MagicPenetrationPercent],
+        attacker[StatKind::MagicPenetration],
+    );
+
+    Mitigation {
+        target: *target,
+        effective_armor,
+        effective_magic_resistance,
+        physical: damage_multiplier(effective_armor),
+        magic: damage_multiplier(effective_magic_resistance),
+        true_damage: 1.0,
+    }
+}
+
+/// stores how much raw damage it takes to kill a champion, shields included
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveHealth {
+    pub health: f64,
+    pub shield: f64,
+    pub physical: f64,
+    pub magic: f64,
+    /// damage split between physical and magic by the physical share
+    pub mixed: f64,
+    pub physical_share: f64,
+    #[serde(rename = "true")]
+    pub true_damage: f64,
+}
+
+/// Computes a defender's effective health against an attacker's penetration. The physical share
+/// is the portion of mixed damage that is physical, from 0 to 1.
+pub fn effective_health(defender: &ResolvedStats, attacker: &ResolvedStats, physical_share: f64) -> EffectiveHealth {
+    let physical_share = physical_share.clamp(0.0, 1.0);
+    let health = defender[StatKind::Health];
+    let shield = defender[StatKind::Shield];
+    let total = health + shield;
+
+    let mitigation = mitigation(attacker, &TargetStats::from_resolved(defender), &Reductions::default());
+    let mixed_multiplier = physical_share * mitigation.physical + (1.0 - physical_share) * mitigation.magic;
+
+    EffectiveHealth {
+        health,
+        shield,
+        physical: total / mitigation.physical,
+        magic: total / mitigation.magic,
+        mixed: total / mixed_multiplier,
+        physical_share,
+        true_damage: total,
+    }
+}
+
+/// This structure is only useful for reading the body of player_effective_health.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveHealthRequest {
+    /// enemy whose penetration is applied, if any
+    enemy: Option<Enemy>,
+    #[serde(default = "default_physical_share")]
+    physical_share: f64,
+    /// shields not granted by the build, such as from an ally
+    #[serde(default)]
+    shield: f64,
+}
+
+fn default_physical_share() -> f64 {
+    0.5
+}
+
+/// Shows the player's effective health, optionally against a given enemy's penetration.
+pub async fn player_effective_health(req: HttpRequest, sessions: web::Data<Sessions>, game_data: web::Data<SharedGameData>, body: web::Json<EffectiveHealthRequest>) -> impl actix_web::Responder {
+    let request = body.into_inner();
+    let data = game_data.get();
+
+    let attacker = match &request.enemy {
+        Some(enemy) => match enemy.resolved_stats(&data) {
+            Ok(resolved) => resolved,
+            Err(err) => return HttpResponse::BadRequest().body(err),
+        },
+        None => ResolvedStats::new(),
+    };
+
+    let session = sessions.for_request(&req).await;
+    let session = session.lock().await;
+    let player = &session.player;
+    let mut defender = player.resolved_stats(&data);
+    defender[StatKind::Shield] += request.shield;
+
+    HttpResponse::Ok().json(effective_health(&defender, &attacker, request.physical_share))
+}
+
+/// This structure is only useful for reading the body of player_damage.
+#[derive(Deserialize)]
+pub struct DamageRequest {
+    target: Target,
+    #[serde(default)]
+    reductions: Reductions,
+}
+
+/// Shows how much of the player's damage gets through to a given target.
+pub async fn player_damage(req: HttpRequest, sessions: web::Data<Sessions>, game_data: web::Data<SharedGameData>, body: web::Json<DamageRequest>) -> impl actix_web::Responder {
+    let request = body.into_inner();
+    let data = game_data.get();
+
+    let target = match request.target.resolve(&data) {
+        Ok(target) => target,
+        Err(err) => return HttpResponse::BadRequest().body(err),
+    };
+
+    let session = sessions.for_request(&req).await;
+    let session = session.lock().await;
+    let player = &session.player;
+    let attacker = player.resolved_stats(&data);
+
+    HttpResponse::Ok().json(mitigation(&attacker, &target, &request.reductions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamedata::tests::sample;
+
+    #[test]
+    fn targets_reject_unknown_fields() {
+        let target: Target = serde_json::from_str(r#"{ "armor": 50.0 }"#).unwrap();
+        assert!(matches!(target, Target::Dummy(Dummy { armor, .. }) if armor == 50.0));
+        let target: Target = serde_json::from_str(r#"{ "champion": "Garen", "level": 6 }"#).unwrap();
+        assert!(matches!(target, Target::Enemy(_)));
+
+        assert!(serde_json::from_str::<Target>(r#"{ "champion": "Garen" }"#).is_err());
+        assert!(serde_json::from_str::<Target>(r#"{ "armour": 50.0 }"#).is_err());
+    }
+
+    #[test]
+    fn enemies_are_resolved_from_the_game_data() {
+        let data = sample();
+        let enemy = Enemy { champion: "Garen".to_string(), level: 1, items: vec![3075] };
+        let target = Target::Enemy(enemy.clone()).resolve(&data).unwrap();
+        assert_eq!(target.armor, 38.0 + 75.0);
+        assert_eq!(target.health, 690.0 + 150.0);
+
+        assert!(Enemy { level: 19, ..enemy.clone() }.resolved_stats(&data).is_err());
+        assert_eq!(Enemy { items: vec![9999], ..enemy }.resolved_stats(&data).unwrap_err(), "Unknown item 9999");
+    }
+
+    #[test]
+    fn penetration_is_applied_after_reduction() {
+        assert!((effective_resistance(100.0, 10.0, 0.0, 30.0, 10.0) - 53.0).abs() < 1e-9);
+        assert_eq!(effective_resistance(100.0, 0.0, 30.0, 0.0, 0.0), 70.0);
+        assert!((effective_resistance(100.0, 0.0, 30.0, 40.0, 0.0) - 42.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn penetration_cannot_go_below_zero() {
+        assert_eq!(effective_resistance(20.0, 0.0, 0.0, 0.0, 30.0), 0.0);
+        assert_eq!(effective_resistance(20.0, 30.0, 0.0, 0.0, 30.0), -10.0);
+    }
+
+    #[test]
+    fn negative_resistance_amplifies_damage() {
+        assert_eq!(damage_multiplier(0.0), 1.0);
+        assert_eq!(damage_multiplier(100.0), 0.5);
+        assert!((damage_multiplier(-20.0) - (2.0 - 100.0 / 120.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn mitigation_uses_attacker_penetration() {
+        let mut attacker = ResolvedStats::new();
+        attacker[StatKind::ArmorPenetration] = 30.0;
+        attacker[StatKind::Lethality] = 10.0;
+        attacker[StatKind::MagicPenetration] = 15.0;
+        let target = TargetStats { armor: 100.0, magic_resistance: 50.0, health: 2000.0 };
+
+        let mitigation = mitigation(&attacker, &target, &Reductions::default());
+        assert_eq!(mitigation.effective_armor, 60.0);
+        assert_eq!(mitigation.physical, 0.625);
+        assert_eq!(mitigation.effective_magic_resistance, 35.0);
+        assert_eq!(mitigation.true_damage, 1.0);
+    }
+
+    #[test]
+    fn effective_health_includes_shields_and_penetration() {
+        let mut defender = ResolvedStats::new();
+        defender[StatKind::Health] = 2000.0;
+        defender[StatKind::Shield] = 200.0;
+        defender[StatKind::Armor] = 100.0;
+        defender[StatKind::MagicResistance] = 50.0;
+
+        let unmitigated = effective_health(&defender, &ResolvedStats::new(), 0.5);
+        assert_eq!(unmitigated.physical, 4400.0);
+        assert_eq!(unmitigated.magic, 3300.0);
+        assert!((unmitigated.mixed - 2200.0 / (0.5 * 0.5 + 0.5 / 1.5)).abs() < 1e-9);
+        assert_eq!(unmitigated.true_damage, 2200.0);
+
+        let mut attacker = ResolvedStats::new();
+        attacker[StatKind::ArmorPenetration] = 50.0;
+        assert_eq!(effective_health(&defender, &attacker, 1.0).mixed, 3300.0);
+    }
+
+    #[test]
+    fn targets_deserialize_by_shape() {
+        let enemy: Target = serde_json::from_str(r#"{ "champion": "Garen", "level": 11, "items": [3075] }"#).unwrap();
+        assert!(matches!(enemy, Target::Enemy(ref enemy) if enemy.level == 11));
+        let dummy: Target = serde_json::from_str(r#"{ "armor": 80, "magicResistance": 40 }"#).unwrap();
+        assert!(matches!(dummy, Target::Dummy(ref dummy) if dummy.magic_resistance == 40.0 && dummy.health == 1000.0));
+    }
+}
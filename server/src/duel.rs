@@ -0,0 +1,478 @@
+/*
+ * File: duel.rs
+ *
+ * Copyright (c) 2025 Alan Fung
+ *
+ * Description: simulates a fight between two builds that stand still and trade attacks and
+ * abilities
+ */
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::ability::{self, Ability};
+use crate::combat::{self, Attacker, DamageType, OnHit, Spellblade, SPELLBLADE_COOLDOWN};
+use crate::damage::{mitigation, Mitigation, Reductions, TargetStats};
+use crate::evaluate::Build;
+use crate::gamedata::{GameData, SharedGameData};
+use crate::stats::StatKind;
+
+/// seconds between the moments the simulation looks at
+pub const TIME_STEP: f64 = 0.05;
+/// longest duel that can be simulated, in seconds
+const MAX_DURATION: f64 = 120.0;
+
+/// which of the two builds in a duel
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Side {
+    First,
+    Second,
+}
+
+/// what happened in an event
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum EventKind {
+    Attack,
+    Ability,
+    Death,
+}
+
+/// stores something that happened during a duel
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    pub time: f64,
+    /// side that acted, or that died
+    pub side: Side,
+    pub kind: EventKind,
+    pub source: String,
+    /// damage after resistances, including damage absorbed by shields
+    pub damage: f64,
+    /// health restored to the side that acted by lifesteal and omnivamp
+    pub healing: f64,
+    /// health of the other side afterwards
+    pub target_health: f64,
+}
+
+/// stores how one side of a duel did
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FighterResult {
+    pub champion: String,
+    pub health: f64,
+    pub max_health: f64,
+    pub damage_dealt: f64,
+    /// health restored by lifesteal, physical vamp, omnivamp and health regeneration
+    pub healing: f64,
+    pub shield_absorbed: f64,
+    pub attacks: u32,
+    pub casts: u32,
+    /// how much of this side's damage gets through to the other side
+    pub mitigation: Mitigation,
+}
+
+/// stores the outcome of a duel
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DuelResult {
+    /// side left standing, or None when both or neither die
+    pub winner: Option<Side>,
+    /// seconds until the first death, if any
+    pub time_to_kill: Option<f64>,
+    pub first: FighterResult,
+    pub second: FighterResult,
+    pub events: Vec<Event>,
+}
+
+/// damage dealt by one action, along with the parts lifesteal and physical vamp apply to
+struct Action {
+    kind: EventKind,
+    source: String,
+    damage: f64,
+    attack_damage: f64,
+    physical_damage: f64,
+}
+
+/// stores a build taking part in a duel and its state as the duel goes on
+struct Fighter<'a> {
+    attacker: Attacker,
+    abilities: &'a [Option<Ability>; 4],
+    skill_points: [u8; 4],
+    effects: Vec<&'static OnHit>,
+    spellblade: Option<&'static Spellblade>,
+    /// damage multipliers against the other side
+    mitigation: Mitigation,
+    health: f64,
+    max_health: f64,
+    shield: f64,
+    next_attack: f64,
+    ready_at: [f64; 4],
+    spellblade_ready_at: f64,
+    empowered: bool,
+    result: FighterResult,
+}
+
+impl<'a> Fighter<'a> {
+    /// Prepares a build to fight an opponent with the given stats.
+    fn new(attacker: Attacker, abilities: &'a [Option<Ability>; 4], skill_points: [u8; 4], opponent: &TargetStats) -> Self {
+        let health = attacker.stats[StatKind::Health];
+        let mitigation = mitigation(&attacker.stats, opponent, &Reductions::default());
+
+        Fighter {
+            effects: attacker.items.iter().filter_map(|&item| combat::on_hit(item)).collect(),
+            spellblade: combat::spellblade(&attacker.items),
+            health,
+            max_health: health,
+            shield: attacker.stats[StatKind::Shield],
+            next_attack: 0.0,
+            ready_at: [0.0; 4],
+            spellblade_ready_at: 0.0,
+            empowered: false,
+            result: FighterResult {
+                champion: attacker.champion.clone(),
+                health,
+                max_health: health,
+                damage_dealt: 0.0,
+                healing: 0.0,
+                shield_absorbed: 0.0,
+                attacks: 0,
+                casts: 0,
+                mitigation: mitigation.clone(),
+            },
+            mitigation,
+            skill_points,
+            abilities,
+            attacker,
+        }
+    }
+
+    fn alive(&self) -> bool {
+        self.health > 0.0
+    }
+
+    /// Casts every ability that is ready and attacks if the next attack is due, against a target
+    /// with the given current and maximum health.
+    fn act(&mut self, time: f64, target_health: f64, target_max_health: f64) -> Vec<Action> {
+        let mut actions = Vec::new();
+        let haste = self.attacker.stats[StatKind::AbilityHaste];
+
+        for (index, ability) in self.abilities.iter().enumerate() {
+            let Some(ability) = ability else { continue };
+            let rank = self.skill_points[index];
+            if rank == 0 || ability.damage.is_empty() || time < self.ready_at[index] {
+                continue;
+            }
+
+            let raw = ability.raw_damage(&self.attacker, rank, target_health, target_max_health);
+            let damage = raw * ability.damage_type.multiplier(&self.mitigation);
+            actions.push(Action {
+                kind: EventKind::Ability,
+                source: ability.name.clone(),
+                damage,
+                attack_damage: 0.0,
+                physical_damage: if ability.damage_type == DamageType::Physical { damage } else { 0.0 },
+            });
+            // abilities without cooldown data are cast once rather than every step
+            self.ready_at[index] = match ability::cooldown(ability, rank, haste) {
+                Some(cooldown) => time + cooldown.max(TIME_STEP),
+                None => f64::INFINITY,
+            };
+            self.result.casts += 1;
+            if self.spellblade.is_some() && time >= self.spellblade_ready_at {
+                self.empowered = true;
+            }
+        }
+
+        if time >= self.next_attack {
+            self.result.attacks += 1;
+            let attacks = self.result.attacks;
+            let attack_damage = self.attacker.attack_damage() * self.mitigation.physical;
+            let mut hits = vec![(DamageType::Physical, attack_damage)];
+
+            if let Some(spellblade) = self.spellblade.filter(|_| self.empowered) {
+                hits.push((spellblade.damage_type, spellblade.raw_damage(&self.attacker) * spellblade.damage_type.multiplier(&self.mitigation)));
+                self.empowered = false;
+                self.spellblade_ready_at = time + SPELLBLADE_COOLDOWN;
+            }
+
+            let repeated = self.effects.iter().any(|effect| effect.repeat_every != 0 && attacks.is_multiple_of(effect.repeat_every));
+            for effect in self.effects.iter().filter(|effect| attacks.is_multiple_of(effect.every)) {
+                let applications = if repeated && effect.every == 1 { 2.0 } else { 1.0 };
+                hits.push((effect.damage_type, effect.raw_damage(&self.attacker, target_health, target_max_health) * effect.damage_type.multiplier(&self.mitigation) * applications));
+            }
+
+            let stacks: f64 = self.effects.iter()
+                .map(|effect| effect.attack_speed_per_stack * attacks.min(effect.max_stacks) as f64)
+                .sum();
+            self.next_attack += 1.0 / self.attacker.attacks_per_second(stacks);
+
+            actions.push(Action {
+                kind: EventKind::Attack,
+                source: "Auto attack".to_string(),
+                damage: hits.iter().map(|(_, damage)| damage).sum(),
+                attack_damage,
+                physical_damage: hits.iter().filter(|(damage_type, _)| *damage_type == DamageType::Physical).map(|(_, damage)| damage).sum(),
+            });
+        }
+
+        actions
+    }
+
+    /// Takes damage, which shields absorb first.
+    fn take(&mut self, damage: f64) {
+        let absorbed = damage.min(self.shield);
+        self.shield -= absorbed;
+        self.result.shield_absorbed += absorbed;
+        self.health -= damage - absorbed;
+    }
+
+    /// Restores health without going over the maximum, returning the amount restored.
+    fn heal(&mut self, amount: f64) -> f64 {
+        let healed = amount.min(self.max_health - self.health).max(0.0);
+        self.health += healed;
+        self.result.healing += healed;
+        healed
+    }
+
+    /// Works out how much an action heals through lifesteal, physical vamp and omnivamp.
+    fn vamp(&self, action: &Action) -> f64 {
+        let stats = &self.attacker.stats;
+        action.attack_damage * stats[StatKind::Lifesteal] / 100.0
+            + action.physical_damage * stats[StatKind::PhysicalVamp] / 100.0
+            + action.damage * stats[StatKind::Omnivamp] / 100.0
+    }
+}
+
+/// Simulates a duel between two builds for up to a number of seconds. Both sides start at full
+/// health next to each other, attack whenever they can and cast every ranked damaging ability as
+/// soon as it comes off cooldown. Both sides act at the same moments, so they can die together.
+pub fn duel(data: &GameData, first: &Build, second: &Build, duration: f64) -> Result<DuelResult, String> {
+    if !(duration > 0.0 && duration <= MAX_DURATION) {
+        return Err(format!("Duration must be between 0 and {} seconds", MAX_DURATION));
+    }
+
+    let prepare = |build: &Build| -> Result<(Attacker, &[Option<Ability>; 4]), String> {
+        let (sources, _) = build.sources(data)?;
+        let abilities = data.abilities(&build.champion).ok_or_else(|| format!("No ability data for {}", build.champion))?;
+        Ok((Attacker::from_sources(&sources, build.level, &build.champion), abilities))
+    };
+    let (first_attacker, first_abilities) = prepare(first)?;
+    let (second_attacker, second_abilities) = prepare(second)?;
+    let first_stats = TargetStats::from_resolved(&first_attacker.stats);
+    let second_stats = TargetStats::from_resolved(&second_attacker.stats);
+
+    let mut fighters = [
+        Fighter::new(first_attacker, first_abilities, first.skill_points, &second_stats),
+        Fighter::new(second_attacker, second_abilities, second.skill_points, &first_stats),
+    ];
+
+    let sides = [Side::First, Side::Second];
+    let mut events = Vec::new();
+    let mut time = 0.0;
+    let mut time_to_kill = None;
+
+    while time <= duration {
+        let actions: Vec<Vec<Action>> = (0..2)
+            .map(|i| {
+                let (health, max_health) = (fighters[1 - i].health, fighters[1 - i].max_health);
+                fighters[i].act(time, health, max_health)
+            })
+            .collect();
+
+        for (i, actions) in actions.into_iter().enumerate() {
+            for action in actions {
+                fighters[1 - i].take(action.damage);
+                let healing = fighters[i].heal(fighters[i].vamp(&action));
+                fighters[i].result.damage_dealt += action.damage;
+
+                events.push(Event {
+                    time,
+                    side: sides[i],
+                    kind: action.kind,
+                    source: action.source,
+                    damage: action.damage,
+                    healing,
+                    target_health: fighters[1 - i].health.max(0.0),
+                });
+            }
+        }
+
+        for (i, fighter) in fighters.iter_mut().enumerate() {
+            if fighter.alive() {
+                fighter.heal(fighter.attacker.stats[StatKind::HealthRegen] / 5.0 * TIME_STEP);
+            } else {
+                events.push(Event {
+                    time,
+                    side: sides[i],
+                    kind: EventKind::Death,
+                    source: fighter.result.champion.clone(),
+                    damage: 0.0,
+                    healing: 0.0,
+                    target_health: 0.0,
+                });
+            }
+        }
+
+        if fighters.iter().any(|fighter| !fighter.alive()) {
+            time_to_kill = Some(time);
+            break;
+        }
+        time += TIME_STEP;
+    }
+
+    let winner = match (fighters[0].alive(), fighters[1].alive()) {
+        (true, false) => Some(Side::First),
+        (false, true) => Some(Side::Second),
+        _ => None,
+    };
+    let [first, second] = fighters.map(|mut fighter| {
+        fighter.result.health = fighter.health.max(0.0);
+        fighter.result
+    });
+
+    Ok(DuelResult { winner, time_to_kill, first, second, events })
+}
+
+/// This structure is only useful for reading the body of simulate_duel.
+#[derive(Deserialize)]
+pub struct DuelRequest {
+    first: Build,
+    second: Build,
+    /// seconds the duel can last
+    #[serde(default = "default_duration")]
+    duration: f64,
+}
+
+fn default_duration() -> f64 {
+    30.0
+}
+
+/// Simulates a duel between the two builds described in the body.
+pub async fn simulate_duel(game_data: web::Data<SharedGameData>, body: web::Json<DuelRequest>) -> impl actix_web::Responder {
+    let request = body.into_inner();
+    match duel(&game_data.get(), &request.first, &request.second, request.duration) {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(err) => HttpResponse::BadRequest().body(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamedata::tests::sample;
+
+    fn build(champion: &str, level: u8, items: &[u16], skill_points: [u8; 4]) -> Build {
+        Build { champion: champion.to_string(), level, items: items.to_vec(), skill_points }
+    }
+
+    #[test]
+    fn stronger_builds_win() {
+        let data = sample();
+        let garen = build("Garen", 18, &[3031, 3072, 3046], [5, 5, 5, 3]);
+        let annie = build("Annie", 1, &[], [1, 0, 0, 0]);
+
+        let result = duel(&data, &annie, &garen, 30.0).unwrap();
+        assert_eq!(result.winner, Some(Side::Second));
+        assert!(result.time_to_kill.unwrap() < 5.0);
+        assert_eq!(result.first.health, 0.0);
+        assert_eq!(result.events.last().unwrap().kind, EventKind::Death);
+    }
+
+    #[test]
+    fn mirror_matches_are_draws() {
+        let data = sample();
+        let garen = build("Garen", 9, &[3031], [5, 1, 2, 1]);
+        let result = duel(&data, &garen, &garen, 60.0).unwrap();
+        assert_eq!(result.winner, None);
+        assert_eq!(result.first, result.second);
+        assert!(result.time_to_kill.is_some());
+    }
+
+    #[test]
+    fn lifesteal_heals() {
+        let data = sample();
+        let bloodthirster = build("Garen", 11, &[3072], [5, 1, 3, 2]);
+        let long_swords = build("Garen", 11, &[1036, 1036, 1036, 1036, 1036, 1036], [5, 1, 3, 2]);
+
+        let result = duel(&data, &bloodthirster, &long_swords, 60.0).unwrap();
+        assert!(result.first.healing > result.second.healing);
+        assert!(result.events.iter().any(|event| event.side == Side::First && event.healing > 0.0));
+        assert!(result.events.iter().all(|event| event.side == Side::First || event.healing == 0.0));
+    }
+
+    #[test]
+    fn shields_absorb_damage_first() {
+        let data = sample();
+        let (sources, _) = build("Garen", 1, &[], [0; 4]).sources(&data).unwrap();
+        let mut attacker = Attacker::from_sources(&sources, 1, "Garen");
+        attacker.stats[StatKind::Shield] = 100.0;
+
+        let target = TargetStats { armor: 0.0, magic_resistance: 0.0, health: 1000.0 };
+        let mut garen = Fighter::new(attacker, data.abilities("Garen").unwrap(), [0; 4], &target);
+        garen.take(60.0);
+        assert_eq!(garen.health, 690.0);
+        garen.take(60.0);
+        assert_eq!(garen.health, 670.0);
+        assert_eq!(garen.result.shield_absorbed, 100.0);
+    }
+
+    #[test]
+    fn abilities_wait_for_their_cooldowns() {
+        let data = sample();
+        let garen = build("Garen", 1, &[], [1, 0, 0, 0]);
+        let result = duel(&data, &garen, &build("Annie", 1, &[], [0, 0, 0, 0]), 20.0).unwrap();
+
+        let casts: Vec<f64> = result.events.iter()
+            .filter(|event| event.side == Side::First && event.kind == EventKind::Ability)
+            .map(|event| event.time)
+            .collect();
+        assert!(casts.windows(2).all(|pair| pair[1] - pair[0] >= 8.0 - 1e-9));
+        assert_eq!(result.first.casts as usize, casts.len());
+    }
+
+    #[test]
+    fn abilities_without_cooldowns_are_cast_once() {
+        let data = sample();
+        let (sources, _) = build("Garen", 1, &[], [1, 0, 0, 0]).sources(&data).unwrap();
+        let attacker = Attacker::from_sources(&sources, 1, "Garen");
+        let mut abilities = data.abilities("Garen").unwrap().clone();
+        abilities[0].as_mut().unwrap().cooldown.clear();
+
+        let target = TargetStats { armor: 0.0, magic_resistance: 0.0, health: 1000.0 };
+        let mut garen = Fighter::new(attacker, &abilities, [1, 0, 0, 0], &target);
+        let casts: usize = (0..100)
+            .map(|step| garen.act(step as f64 * TIME_STEP, 1000.0, 1000.0))
+            .map(|actions| actions.iter().filter(|action| action.kind == EventKind::Ability).count())
+            .sum();
+        assert_eq!(casts, 1);
+    }
+
+    #[test]
+    fn physical_vamp_heals_from_physical_damage() {
+        let data = sample();
+        let (sources, _) = build("Garen", 1, &[], [0; 4]).sources(&data).unwrap();
+        let mut attacker = Attacker::from_sources(&sources, 1, "Garen");
+        attacker.stats[StatKind::PhysicalVamp] = 10.0;
+
+        let target = TargetStats { armor: 0.0, magic_resistance: 0.0, health: 1000.0 };
+        let garen = Fighter::new(attacker, data.abilities("Garen").unwrap(), [0; 4], &target);
+        let action = |physical_damage: f64| Action {
+            kind: EventKind::Ability,
+            source: "Decisive Strike".to_string(),
+            damage: 100.0,
+            attack_damage: 0.0,
+            physical_damage,
+        };
+        assert_eq!(garen.vamp(&action(100.0)), 10.0);
+        assert_eq!(garen.vamp(&action(0.0)), 0.0);
+    }
+
+    #[test]
+    fn invalid_duels_are_errors() {
+        let data = sample();
+        let garen = build("Garen", 1, &[], [0; 4]);
+        assert!(duel(&data, &garen, &garen, 0.0).is_err());
+        assert!(duel(&data, &garen, &build("Teemo", 1, &[], [0; 4]), 10.0).is_err());
+    }
+}
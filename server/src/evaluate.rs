@@ -0,0 +1,411 @@
+/*
+ * File: evaluate.rs
+ *
+ * Copyright (c) 2025 Alan Fung
+ *
+ * Description: evaluates builds described in a request, without touching any session's player
+ */
+use actix_web::http::header;
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::thread;
+
+use crate::breakdown::{self, Source, SourceKind};
+use crate::damage::{self, Dummy, EffectiveHealth, Mitigation, Reductions, Target, TargetStats};
+use crate::gamedata::{GameData, SharedGameData};
+use crate::player::{MAX_LEVEL, MIN_LEVEL};
+use crate::skills;
+use crate::stats::{self, AttackSpeed, ResolvedStats};
+
+/// describes a champion at a level with items
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Build {
+    pub champion: String,
+    pub level: u8,
+    #[serde(default)]
+    pub items: Vec<u16>,
+    #[serde(default)]
+    pub skill_points: [u8; 4],
+}
+
+/// stores everything worth knowing about a build
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Evaluation {
+    pub build: Build,
+    pub stats: ResolvedStats,
+    pub attack_speed: AttackSpeed,
+    /// effective health against a build without penetration, with damage split evenly
+    pub effective_health: EffectiveHealth,
+    /// how much of the build's damage gets through to the target
+    pub damage: Mitigation,
+    /// total cost of the build's items
+    pub gold: u32,
+}
+
+impl Build {
+    /// Lists the build's stat sources, rejecting unknown champions and items and invalid levels
+    /// or skill points. Also returns the total cost of the items.
+    pub fn sources(&self, data: &GameData) -> Result<(Vec<Source>, u32), String> {
+        if !(MIN_LEVEL..=MAX_LEVEL).contains(&self.level) {
+            return Err(format!("Level must be between {} and {}", MIN_LEVEL, MAX_LEVEL));
+        }
+        if self.items.len() > 6 {
+            return Err("A build can have at most 6 items".to_string());
+        }
+
+        let base_stats = data.champion(&self.champion).ok_or_else(|| format!("Unknown champion {}", self.champion))?;
+        skills::validate(&self.champion, &self.skill_points, self.level)?;
+
+        let mut sources = vec![Source {
+            kind: SourceKind::Champion,
+            id: self.champion.clone(),
+            name: self.champion.clone(),
+            stats: base_stats.clone(),
+        }];
+        sources.extend(data.item_sources(&self.items)?);
+
+        let gold = self.items.iter().filter_map(|&item| data.item(item)).map(|item| item.gold).sum();
+        Ok((sources, gold))
+    }
+
+    /// Resolves the build's stats and what they add up to against a given target, after the
+    /// target's resistances are reduced.
+    pub fn evaluate(&self, data: &GameData, target: &TargetStats, reductions: &Reductions) -> Result<Evaluation, String> {
+        let (sources, gold) = self.sources(data)?;
+        let resolved = breakdown::resolve(&sources, self.level, &self.champion);
+        let attack_speed = stats::attack_speed(&sources[0].stats, &breakdown::bonus_stats(&sources), self.level, &self.champion);
+
+        Ok(Evaluation {
+            build: self.clone(),
+            attack_speed,
+            effective_health: damage::effective_health(&resolved, &ResolvedStats::new(), 0.5),
+            damage: damage::mitigation(&resolved, target, reductions),
+            stats: resolved,
+            gold,
+        })
+    }
+}
+
+/// stores how a build differs from the first build in a comparison
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Delta {
+    pub stats: ResolvedStats,
+    pub physical_effective_health: f64,
+    pub magic_effective_health: f64,
+    pub physical_damage: f64,
+    pub magic_damage: f64,
+    pub gold: i64,
+}
+
+impl Delta {
+    /// Subtracts a baseline evaluation from an evaluation.
+    pub fn between(baseline: &Evaluation, evaluation: &Evaluation) -> Self {
+        Delta {
+            stats: &evaluation.stats - &baseline.stats,
+            physical_effective_health: evaluation.effective_health.physical - baseline.effective_health.physical,
+            magic_effective_health: evaluation.effective_health.magic - baseline.effective_health.magic,
+            physical_damage: evaluation.damage.physical - baseline.damage.physical,
+            magic_damage: evaluation.damage.magic - baseline.damage.magic,
+            gold: evaluation.gold as i64 - baseline.gold as i64,
+        }
+    }
+}
+
+/// This structure is only useful for reading the body of evaluate_build.
+#[derive(Deserialize)]
+pub struct EvaluateRequest {
+    #[serde(flatten)]
+    build: Build,
+    /// target the build's damage is dealt to, which defaults to a dummy without resistances
+    #[serde(default)]
+    target: Option<Target>,
+    #[serde(default)]
+    reductions: Reductions,
+}
+
+/// Evaluates the build described in the body. Nothing is read from or written to the session, so
+/// any number of builds can be evaluated at once.
+pub async fn evaluate_build(game_data: web::Data<SharedGameData>, body: web::Json<EvaluateRequest>) -> impl actix_web::Responder {
+    let request = body.into_inner();
+
+    let data = game_data.get();
+
+    let target = request.target.unwrap_or_else(|| Target::Dummy(Dummy::default()));
+    let target = match target.resolve(&data) {
+        Ok(target) => target,
+        Err(err) => return HttpResponse::BadRequest().body(err),
+    };
+
+    match request.build.evaluate(&data, &target, &request.reductions) {
+        Ok(evaluation) => HttpResponse::Ok().json(evaluation),
+        Err(err) => HttpResponse::BadRequest().body(err),
+    }
+}
+
+/// This structure is only useful for reading the body of compare_builds.
+#[derive(Deserialize)]
+pub struct CompareRequest {
+    builds: Vec<Build>,
+    /// target the builds' damage is dealt to, which defaults to a dummy without resistances
+    #[serde(default)]
+    target: Option<Target>,
+    #[serde(default)]
+    reductions: Reductions,
+}
+
+/// This structure is only useful for displaying the output of compare_builds.
+#[derive(Serialize)]
+struct ComparedBuild {
+    #[serde(flatten)]
+    evaluation: Evaluation,
+    /// difference from the first build
+    delta: Delta,
+}
+
+/// Compares two or more builds side by side, measuring each against the first.
+pub async fn compare_builds(game_data: web::Data<SharedGameData>, body: web::Json<CompareRequest>) -> impl actix_web::Responder {
+    let request = body.into_inner();
+    if request.builds.len() < 2 {
+        return HttpResponse::BadRequest().body("At least two builds are needed for a comparison");
+    }
+
+    let data = game_data.get();
+    let target = request.target.unwrap_or_else(|| Target::Dummy(Dummy::default()));
+    let target = match target.resolve(&data) {
+        Ok(target) => target,
+        Err(err) => return HttpResponse::BadRequest().body(err),
+    };
+
+    let mut evaluations = Vec::with_capacity(request.builds.len());
+    for (i, build) in request.builds.iter().enumerate() {
+        match build.evaluate(&data, &target, &request.reductions) {
+            Ok(evaluation) => evaluations.push(evaluation),
+            Err(err) => return HttpResponse::BadRequest().body(format!("Build {}: {}", i + 1, err)),
+        }
+    }
+
+    let compared: Vec<ComparedBuild> = evaluations.iter()
+        .map(|evaluation| ComparedBuild {
+            delta: Delta::between(&evaluations[0], evaluation),
+            evaluation: evaluation.clone(),
+        })
+        .collect();
+
+    HttpResponse::Ok().json(compared)
+}
+
+/// content type of bodies with one JSON value per line
+const NDJSON: &str = "application/x-ndjson";
+
+/// stores the result of evaluating one build in a batch
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum BatchEntry {
+    Ok(Box<Evaluation>),
+    Error(String),
+}
+
+/// Evaluates builds spread across every available thread, returning the results in order. Builds
+/// that could not be read are passed in as errors and kept in place.
+pub fn evaluate_batch(data: &GameData, builds: &[Result<Build, String>], target: &TargetStats, reductions: &Reductions) -> Vec<BatchEntry> {
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let chunk_size = builds.len().div_ceil(threads).max(1);
+
+    thread::scope(|scope| {
+        let chunks: Vec<_> = builds.chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || {
+                chunk.iter()
+                    .map(|build| match build.as_ref().map_err(Clone::clone).and_then(|build| build.evaluate(data, target, reductions)) {
+                        Ok(evaluation) => BatchEntry::Ok(Box::new(evaluation)),
+                        Err(err) => BatchEntry::Error(err),
+                    })
+                    .collect::<Vec<_>>()
+            }))
+            .collect();
+
+        chunks.into_iter()
+            .flat_map(|chunk| chunk.join().expect("Batch evaluation panicked"))
+            .collect()
+    })
+}
+
+/// This structure is only useful for reading the JSON body of evaluate_builds.
+#[derive(Deserialize)]
+pub struct BatchRequest {
+    /// builds are read one at a time, so that a malformed build only fails its own entry
+    builds: Vec<Value>,
+    #[serde(default)]
+    target: Option<Target>,
+    #[serde(default)]
+    reductions: Reductions,
+}
+
+/// Reads a build, describing what is wrong with it if it cannot be read.
+fn read_build(value: Value) -> Result<Build, String> {
+    serde_json::from_value(value).map_err(|e| format!("Invalid build: {}", e))
+}
+
+/// Evaluates many builds at once. The body is either a JSON object with a list of builds, or
+/// NDJSON with one build per line, in which case the results are also returned as NDJSON. Builds
+/// that fail only produce an error in their own entry.
+pub async fn evaluate_builds(req: HttpRequest, game_data: web::Data<SharedGameData>, body: web::Bytes) -> impl actix_web::Responder {
+    let ndjson = req.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with(NDJSON));
+
+    let (builds, target, reductions) = if ndjson {
+        let body = match std::str::from_utf8(&body) {
+            Ok(body) => body,
+            Err(_) => return HttpResponse::BadRequest().body("Body is not valid UTF-8"),
+        };
+        let builds: Vec<_> = body.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(|e| format!("Invalid build: {}", e)))
+            .collect();
+        (builds, None, Reductions::default())
+    } else {
+        let request: BatchRequest = match serde_json::from_slice(&body) {
+            Ok(request) => request,
+            Err(err) => return HttpResponse::BadRequest().body(format!("Invalid batch: {}", err)),
+        };
+        (request.builds.into_iter().map(read_build).collect(), request.target, request.reductions)
+    };
+
+    let data = game_data.get();
+    let target = target.unwrap_or_else(|| Target::Dummy(Dummy::default()));
+    let target = match target.resolve(&data) {
+        Ok(target) => target,
+        Err(err) => return HttpResponse::BadRequest().body(err),
+    };
+
+    let entries = match web::block(move || evaluate_batch(&data, &builds, &target, &reductions)).await {
+        Ok(entries) => entries,
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+
+    if ndjson {
+        let mut body = String::new();
+        for entry in &entries {
+            body.push_str(&serde_json::to_string(entry).unwrap_or_default());
+            body.push('\n');
+        }
+        HttpResponse::Ok().content_type(NDJSON).body(body)
+    } else {
+        HttpResponse::Ok().json(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamedata::tests::sample;
+    use crate::stats::StatKind;
+
+    #[test]
+    fn builds_deserialize_with_defaults() {
+        let build: Build = serde_json::from_str(r#"{ "champion": "Garen", "level": 13 }"#).unwrap();
+        assert!(build.items.is_empty());
+        assert_eq!(build.skill_points, [0; 4]);
+
+        let build: Build = serde_json::from_str(r#"{ "champion": "Garen", "level": 13, "items": [3071], "skillPoints": [5, 1, 3, 2] }"#).unwrap();
+        assert_eq!(build.items, [3071]);
+        assert_eq!(build.skill_points, [5, 1, 3, 2]);
+    }
+
+    #[test]
+    fn evaluate_requests_take_the_build_at_the_top_level() {
+        let request: EvaluateRequest = serde_json::from_str(r#"{
+            "champion": "Garen",
+            "level": 6,
+            "items": [3071],
+            "target": { "armor": 80 },
+            "reductions": { "armorPercent": 30 }
+        }"#).unwrap();
+        assert_eq!(request.build.champion, "Garen");
+        assert_eq!(request.build.items, [3071]);
+        assert!(matches!(request.target, Some(Target::Dummy(ref dummy)) if dummy.armor == 80.0));
+        assert_eq!(request.reductions.armor_percent, 30.0);
+    }
+
+    fn build(champion: &str, level: u8, items: &[u16]) -> Build {
+        Build { champion: champion.to_string(), level, items: items.to_vec(), skill_points: [0; 4] }
+    }
+
+    #[test]
+    fn invalid_builds_are_rejected() {
+        let data = sample();
+        let dummy = TargetStats { armor: 0.0, magic_resistance: 0.0, health: 1000.0 };
+        let evaluate = |build: Build| build.evaluate(&data, &dummy, &Reductions::default()).map(|_| ());
+
+        assert!(evaluate(build("Garen", 19, &[])).is_err());
+        assert!(evaluate(build("Garen", 13, &[1036; 7])).is_err());
+        assert_eq!(evaluate(build("Teemo", 13, &[])).unwrap_err(), "Unknown champion Teemo");
+        assert_eq!(evaluate(build("Garen", 13, &[1036, 9999])).unwrap_err(), "Unknown item 9999");
+        assert!(evaluate(Build { skill_points: [5, 5, 5, 3], ..build("Garen", 13, &[]) }).is_err());
+    }
+
+    #[test]
+    fn evaluates_stats_gold_and_damage() {
+        let data = sample();
+        let target = TargetStats { armor: 100.0, magic_resistance: 0.0, health: 1000.0 };
+        let evaluation = build("Garen", 1, &[1036, 1029, 0]).evaluate(&data, &target, &Reductions::default()).unwrap();
+
+        assert_eq!(evaluation.stats[StatKind::AttackDamage], 79.0);
+        assert_eq!(evaluation.stats[StatKind::Armor], 53.0);
+        assert_eq!(evaluation.gold, 650);
+        assert_eq!(evaluation.damage.physical, 0.5);
+        assert!((evaluation.effective_health.physical - 690.0 * 1.53).abs() < 1e-9);
+    }
+
+    #[test]
+    fn deltas_subtract_the_baseline() {
+        let evaluation = |armor: f64, gold: u32| {
+            let mut stats = ResolvedStats::new();
+            stats[StatKind::Armor] = armor;
+            stats[StatKind::Health] = 1000.0;
+            let target = TargetStats { armor: 50.0, magic_resistance: 0.0, health: 1000.0 };
+            Evaluation {
+                build: Build { champion: "Garen".to_string(), level: 13, items: Vec::new(), skill_points: [0; 4] },
+                attack_speed: AttackSpeed { base: 0.625, bonus: 0.0, total: 0.625 },
+                effective_health: damage::effective_health(&stats, &ResolvedStats::new(), 0.5),
+                damage: damage::mitigation(&stats, &target, &Reductions::default()),
+                stats,
+                gold,
+            }
+        };
+
+        let delta = Delta::between(&evaluation(50.0, 3000), &evaluation(100.0, 2500));
+        assert_eq!(delta.stats[StatKind::Armor], 50.0);
+        assert_eq!(delta.stats[StatKind::Health], 0.0);
+        assert!((delta.physical_effective_health - 500.0).abs() < 1e-9);
+        assert_eq!(delta.magic_effective_health, 0.0);
+        assert_eq!(delta.gold, -500);
+    }
+
+    #[test]
+    fn batches_keep_order_and_per_entry_errors() {
+        let data = sample();
+        let dummy = TargetStats { armor: 0.0, magic_resistance: 0.0, health: 1000.0 };
+        let mut builds: Vec<Result<Build, String>> = (1..=18)
+            .flat_map(|level| [Ok(build("Garen", level, &[1036])), Ok(build("Annie", level, &[3089]))])
+            .collect();
+        builds.insert(5, Ok(build("Teemo", 1, &[])));
+        builds.insert(9, read_build(serde_json::json!({ "champion": "Garen" })));
+
+        let entries = evaluate_batch(&data, &builds, &dummy, &Reductions::default());
+        assert_eq!(entries.len(), builds.len());
+        assert!(matches!(&entries[5], BatchEntry::Error(err) if err == "Unknown champion Teemo"));
+        assert!(matches!(&entries[9], BatchEntry::Error(err) if err.starts_with("Invalid build")));
+        for (entry, build) in entries.iter().zip(&builds) {
+            if let (BatchEntry::Ok(evaluation), Ok(build)) = (entry, build) {
+                assert_eq!(&evaluation.build, build);
+            }
+        }
+        assert_eq!(entries.iter().filter(|entry| matches!(entry, BatchEntry::Ok(_))).count(), 36);
+    }
+}
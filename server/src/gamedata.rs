@@ -0,0 +1,273 @@
+/*
+ * File: gamedata.rs
+ *
+ * Copyright (c) 2025 Alan Fung
+ *
+ * Description: keeps champion and item data in memory, so evaluating builds does not need to read
+ * the caches every time
+ */
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, RwLock};
+
+use crate::ability::{parse_abilities, Ability};
+use crate::breakdown::Source;
+use crate::champion::{latest_patch, parse_base_stats, CHAMP_CACHE_PATH};
+use crate::item::{parse_item, ItemStats, ITEM_CACHE_PATH};
+use crate::stats::Stats;
+
+/// stores the base stats and abilities of every champion and the stats of every item
+#[derive(Debug, Default)]
+pub struct GameData {
+    /// latest patch any champion was changed in, which is the patch the data is from
+    patch: Option<String>,
+    champions: HashMap<String, Stats>,
+    abilities: HashMap<String, [Option<Ability>; 4]>,
+    items: HashMap<u16, ItemStats>,
+}
+
+impl GameData {
+    /// Loads champion and item data from the caches.
+    pub fn load() -> Result<Self, String> {
+        let champions = fs::read_to_string(CHAMP_CACHE_PATH).map_err(|_| "Failed to read champion cache".to_string())?;
+        let items = fs::read_to_string(ITEM_CACHE_PATH).map_err(|_| "Failed to read item cache".to_string())?;
+        Self::from_json(&champions, &items)
+    }
+
+    /// Reads champion and item data in the format of the Meraki data.
+    pub fn from_json(champions: &str, items: &str) -> Result<Self, String> {
+        let champions: HashMap<String, Value> = serde_json::from_str(champions).map_err(|_| "Failed to parse champion data".to_string())?;
+        let items: HashMap<String, Value> = serde_json::from_str(items).map_err(|_| "Failed to parse item data".to_string())?;
+
+        Ok(GameData {
+            patch: latest_patch(champions.values()),
+            champions: champions.iter()
+                .filter_map(|(name, champion)| Some((name.clone(), parse_base_stats(champion)?)))
+                .collect(),
+            abilities: champions.iter()
+                .map(|(name, champion)| (name.clone(), parse_abilities(champion)))
+                .collect(),
+            items: items.values()
+                .filter_map(|item| {
+                    let id = item.get("id").and_then(|v| v.as_u64())? as u16;
+                    Some((id, parse_item(id, item)?))
+                })
+                .collect(),
+        })
+    }
+
+    /// Returns the patch the data is from, if the data says.
+    pub fn patch(&self) -> Option<&str> {
+        self.patch.as_deref()
+    }
+
+    /// Retrieves the base stats of a given champion.
+    pub fn champion(&self, name: &str) -> Option<&Stats> {
+        self.champions.get(name)
+    }
+
+    /// Retrieves the Q, W, E and R of a given champion. Abilities missing from the data are None.
+    pub fn abilities(&self, champion: &str) -> Option<&[Option<Ability>; 4]> {
+        self.abilities.get(champion)
+    }
+
+    /// Retrieves the stats of a given item.
+    pub fn item(&self, id: u16) -> Option<&ItemStats> {
+        self.items.get(&id)
+    }
+
+    /// Lists the names of every champion.
+    pub fn champion_names(&self) -> impl Iterator<Item = &str> {
+        self.champions.keys().map(|name| name.as_str())
+    }
+
+    /// Lists every item along with its id.
+    pub fn items(&self) -> impl Iterator<Item = (u16, &ItemStats)> {
+        self.items.iter().map(|(&id, item)| (id, item))
+    }
+
+    /// Lists an item and its passive as stat sources for each of the given item ids. Fails on
+    /// unknown items, while empty slots are skipped.
+    pub fn item_sources(&self, items: &[u16]) -> Result<Vec<Source>, String> {
+        let mut sources = Vec::new();
+        for &item in items.iter().filter(|&&item| item != 0) {
+            let item_data = self.item(item).ok_or_else(|| format!("Unknown item {}", item))?;
+            sources.extend(item_data.sources(item));
+        }
+        Ok(sources)
+    }
+}
+
+/// stores the game data used by every request, which is swapped out when the caches are updated
+pub struct SharedGameData {
+    data: RwLock<Arc<GameData>>,
+}
+
+impl SharedGameData {
+    pub fn new(data: GameData) -> Self {
+        SharedGameData {
+            data: RwLock::new(Arc::new(data)),
+        }
+    }
+
+    /// Retrieves the current game data. Requests keep the data they started with even if it is
+    /// replaced in the meantime.
+    pub fn get(&self) -> Arc<GameData> {
+        self.data.read().expect("Game data lock poisoned").clone()
+    }
+
+    /// Replaces the game data for requests made from now on.
+    pub fn replace(&self, data: GameData) {
+        *self.data.write().expect("Game data lock poisoned") = Arc::new(data);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::stats::StatKind;
+
+    const CHAMPIONS: &str = r#"{
+        "Garen": { "patchLastChanged": "14.9", "stats": {
+            "health": { "flat": 690.0, "perLevel": 98.0 },
+            "healthRegen": { "flat": 8.0, "perLevel": 0.5 },
+            "armor": { "flat": 38.0, "perLevel": 4.2 },
+            "magicResistance": { "flat": 32.0, "perLevel": 2.05 },
+            "attackDamage": { "flat": 69.0, "perLevel": 4.5 },
+            "attackSpeed": { "flat": 0.625, "perLevel": 3.65 },
+            "attackSpeedRatio": { "flat": 0.625 },
+            "criticalStrikeDamage": { "flat": 175.0 },
+            "criticalStrikeDamageModifier": { "flat": 1.0 },
+            "attackRange": { "flat": 175.0 },
+            "movespeed": { "flat": 340.0 }
+        }, "abilities": {
+            "Q": [{ "name": "Decisive Strike", "damageType": "PHYSICAL_DAMAGE",
+                "effects": [{ "leveling": [{ "attribute": "Bonus Physical Damage", "modifiers": [
+                    { "values": [30, 60, 90, 120, 150], "units": ["", "", "", "", ""] },
+                    { "values": [50, 50, 50, 50, 50], "units": ["% AD", "% AD", "% AD", "% AD", "% AD"] }
+                ] }] }],
+                "cooldown": { "modifiers": [{ "values": [8, 8, 8, 8, 8] }], "affectedByCdr": true } }],
+            "W": [{ "name": "Courage", "damageType": null, "effects": [{ "leveling": [{ "attribute": "Shield Strength", "modifiers": [] }] }],
+                "cooldown": { "modifiers": [{ "values": [23, 21, 19, 17, 15] }], "affectedByCdr": true } }],
+            "E": [{ "name": "Judgment", "damageType": "PHYSICAL_DAMAGE",
+                "effects": [{ "leveling": [{ "attribute": "Physical Damage Per Spin", "modifiers": [
+                    { "values": [4, 8, 12, 16, 20], "units": ["", "", "", "", ""] },
+                    { "values": [32, 34, 36, 38, 40], "units": ["% AD", "% AD", "% AD", "% AD", "% AD"] }
+                ] }] }],
+                "cooldown": { "modifiers": [{ "values": [9, 8, 7, 6, 5] }], "affectedByCdr": true } }],
+            "R": [{ "name": "Demacian Justice", "damageType": "TRUE_DAMAGE",
+                "effects": [{ "leveling": [{ "attribute": "True Damage", "modifiers": [
+                    { "values": [150, 300, 450], "units": ["", "", ""] },
+                    { "values": [25, 30, 35], "units": ["% of target's missing health", "% of target's missing health", "% of target's missing health"] }
+                ] }] }],
+                "cooldown": { "modifiers": [{ "values": [120, 100, 80] }], "affectedByCdr": true } }]
+        } },
+        "Annie": { "patchLastChanged": "14.10", "stats": {
+            "health": { "flat": 560.0, "perLevel": 96.0 },
+            "mana": { "flat": 418.0, "perLevel": 25.0 },
+            "manaRegen": { "flat": 8.0, "perLevel": 0.8 },
+            "armor": { "flat": 23.0, "perLevel": 4.0 },
+            "magicResistance": { "flat": 30.0, "perLevel": 1.3 },
+            "attackDamage": { "flat": 50.0, "perLevel": 2.65 },
+            "attackSpeed": { "flat": 0.61, "perLevel": 1.36 },
+            "attackSpeedRatio": { "flat": 0.625 },
+            "criticalStrikeDamage": { "flat": 175.0 },
+            "criticalStrikeDamageModifier": { "flat": 1.0 },
+            "attackRange": { "flat": 625.0 },
+            "movespeed": { "flat": 335.0 }
+        }, "abilities": {
+            "Q": [{ "name": "Disintegrate", "damageType": "MAGIC_DAMAGE",
+                "effects": [{ "leveling": [{ "attribute": "Magic Damage", "modifiers": [
+                    { "values": [80, 115, 150, 185, 220], "units": ["", "", "", "", ""] },
+                    { "values": [75, 75, 75, 75, 75], "units": ["% AP", "% AP", "% AP", "% AP", "% AP"] }
+                ] }] }],
+                "cooldown": { "modifiers": [{ "values": [4, 4, 4, 4, 4] }], "affectedByCdr": true } }],
+            "W": [{ "name": "Incinerate", "damageType": "MAGIC_DAMAGE",
+                "effects": [{ "leveling": [{ "attribute": "Magic Damage", "modifiers": [
+                    { "values": [70, 115, 160, 205, 250], "units": ["", "", "", "", ""] },
+                    { "values": [85, 85, 85, 85, 85], "units": ["% AP", "% AP", "% AP", "% AP", "% AP"] }
+                ] }] }],
+                "cooldown": { "modifiers": [{ "values": [8, 8, 8, 8, 8] }], "affectedByCdr": true } }],
+            "R": [{ "name": "Summon: Tibbers", "damageType": "MAGIC_DAMAGE",
+                "effects": [{ "leveling": [{ "attribute": "Magic Damage", "modifiers": [
+                    { "values": [150, 275, 400], "units": ["", "", ""] },
+                    { "values": [75, 75, 75], "units": ["% AP", "% AP", "% AP"] }
+                ] }] }],
+                "cooldown": { "modifiers": [{ "values": [130, 115, 100] }], "affectedByCdr": true } }]
+        } }
+    }"#;
+
+    const ITEMS: &str = r#"{
+        "1036": { "id": 1036, "name": "Long Sword", "rank": ["BASIC"], "stats": { "attackDamage": { "flat": 10.0 } }, "shop": { "prices": { "total": 350 } } },
+        "1029": { "id": 1029, "name": "Cloth Armor", "rank": ["BASIC"], "stats": { "armor": { "flat": 15.0 } }, "shop": { "prices": { "total": 300 } } },
+        "1033": { "id": 1033, "name": "Null-Magic Mantle", "rank": ["BASIC"], "stats": { "magicResistance": { "flat": 25.0 } }, "shop": { "prices": { "total": 400 } } },
+        "1028": { "id": 1028, "name": "Ruby Crystal", "rank": ["BASIC"], "stats": { "health": { "flat": 150.0 } }, "shop": { "prices": { "total": 400 } } },
+        "1042": { "id": 1042, "name": "Dagger", "rank": ["BASIC"], "stats": { "attackSpeed": { "flat": 12.0 } }, "shop": { "prices": { "total": 250 } } },
+        "1018": { "id": 1018, "name": "Cloak of Agility", "rank": ["BASIC"], "stats": { "criticalStrikeChance": { "percent": 15.0 } }, "shop": { "prices": { "total": 600 } } },
+        "1052": { "id": 1052, "name": "Amplifying Tome", "rank": ["BASIC"], "stats": { "abilityPower": { "flat": 20.0 } }, "shop": { "prices": { "total": 400 } } },
+        "3089": { "id": 3089, "name": "Rabadon's Deathcap", "rank": ["LEGENDARY"], "stats": { "abilityPower": { "flat": 130.0 } }, "shop": { "prices": { "total": 3600 }, "purchasable": true } },
+        "3031": { "id": 3031, "name": "Infinity Edge", "rank": ["LEGENDARY"], "buildsFrom": [1036, 1018, 1036], "stats": { "attackDamage": { "flat": 65.0 }, "criticalStrikeChance": { "percent": 25.0 } }, "shop": { "prices": { "total": 3450 }, "purchasable": true } },
+        "3133": { "id": 3133, "name": "Caulfield's Warhammer", "rank": ["EPIC"], "buildsFrom": [1036, 1036], "stats": { "attackDamage": { "flat": 25.0 } }, "shop": { "prices": { "total": 1050 }, "purchasable": true } },
+        "3071": { "id": 3071, "name": "Black Cleaver", "rank": ["LEGENDARY"], "buildsFrom": [3133, 1028], "stats": { "attackDamage": { "flat": 40.0 }, "health": { "flat": 400.0 } }, "shop": { "prices": { "total": 3000 }, "purchasable": true } },
+        "3072": { "id": 3072, "name": "Bloodthirster", "rank": ["LEGENDARY"], "stats": { "attackDamage": { "flat": 80.0 }, "lifesteal": { "percent": 15.0 } }, "shop": { "prices": { "total": 3400 }, "purchasable": true } },
+        "3046": { "id": 3046, "name": "Phantom Dancer", "rank": ["LEGENDARY"], "stats": { "attackSpeed": { "flat": 60.0 }, "criticalStrikeChance": { "percent": 25.0 }, "movespeed": { "percent": 8.0 } }, "shop": { "prices": { "total": 2650 }, "purchasable": true } },
+        "3053": { "id": 3053, "name": "Sterak's Gage", "rank": ["LEGENDARY"], "stats": { "health": { "flat": 400.0 } }, "passives": [{ "unique": true, "name": "Lifeline" }], "shop": { "prices": { "total": 3200 }, "purchasable": true } },
+        "3156": { "id": 3156, "name": "Maw of Malmortius", "rank": ["LEGENDARY"], "stats": { "attackDamage": { "flat": 60.0 }, "magicResistance": { "flat": 40.0 } }, "passives": [{ "unique": true, "name": "Lifeline" }], "shop": { "prices": { "total": 3100 }, "purchasable": true } },
+        "3075": { "id": 3075, "name": "Thornmail", "rank": ["LEGENDARY"], "stats": { "health": { "flat": 150.0 }, "armor": { "flat": 75.0 } }, "shop": { "prices": { "total": 2450 }, "purchasable": true } },
+        "3065": { "id": 3065, "name": "Spirit Visage", "rank": ["LEGENDARY"], "stats": { "health": { "flat": 400.0 }, "magicResistance": { "flat": 50.0 } }, "shop": { "prices": { "total": 2900 }, "purchasable": true } },
+        "3006": { "id": 3006, "name": "Berserker's Greaves", "rank": ["BOOTS"], "stats": { "attackSpeed": { "flat": 25.0 }, "movespeed": { "flat": 45.0 } }, "shop": { "prices": { "total": 1100 }, "purchasable": true } },
+        "3047": { "id": 3047, "name": "Plated Steelcaps", "rank": ["BOOTS"], "stats": { "armor": { "flat": 20.0 }, "movespeed": { "flat": 45.0 } }, "shop": { "prices": { "total": 1200 }, "purchasable": true } },
+        "3057": { "id": 3057, "name": "Sheen", "rank": ["EPIC"], "stats": {}, "shop": { "prices": { "total": 900 }, "purchasable": true } },
+        "3115": { "id": 3115, "name": "Nashor's Tooth", "rank": ["LEGENDARY"], "stats": { "abilityPower": { "flat": 80.0 }, "attackSpeed": { "flat": 50.0 } }, "shop": { "prices": { "total": 3000 }, "purchasable": true } },
+        "3124": { "id": 3124, "name": "Guinsoo's Rageblade", "rank": ["LEGENDARY"], "stats": { "attackDamage": { "flat": 30.0 }, "abilityPower": { "flat": 30.0 }, "attackSpeed": { "flat": 25.0 } }, "shop": { "prices": { "total": 3000 }, "purchasable": true } },
+        "3153": { "id": 3153, "name": "Blade of the Ruined King", "rank": ["LEGENDARY"], "stats": { "attackDamage": { "flat": 40.0 }, "attackSpeed": { "flat": 25.0 }, "lifesteal": { "percent": 10.0 } }, "shop": { "prices": { "total": 3200 }, "purchasable": true } },
+        "6672": { "id": 6672, "name": "Kraken Slayer", "rank": ["LEGENDARY"], "stats": { "attackDamage": { "flat": 40.0 }, "attackSpeed": { "flat": 35.0 } }, "shop": { "prices": { "total": 3100 }, "purchasable": true } },
+        "3600": { "id": 3600, "name": "Removed Sword", "rank": ["LEGENDARY"], "removed": true, "stats": { "attackDamage": { "flat": 500.0 } }, "shop": { "prices": { "total": 100 }, "purchasable": true } },
+        "7050": { "id": 7050, "name": "Gangplank Placeholder", "rank": ["LEGENDARY"], "requiredChampion": "Gangplank", "stats": { "attackDamage": { "flat": 500.0 } }, "shop": { "prices": { "total": 100 }, "purchasable": true } }
+    }"#;
+
+    /// Game data with a few champions and items, for tests that need to evaluate builds.
+    pub fn sample() -> GameData {
+        GameData::from_json(CHAMPIONS, ITEMS).unwrap()
+    }
+
+    #[test]
+    fn loads_champions_and_items() {
+        let data = sample();
+        assert_eq!(data.patch(), Some("14.10"));
+        assert_eq!(data.champion("Garen").unwrap()[StatKind::Armor].flat, 38.0);
+        assert!(data.champion("Teemo").is_none());
+
+        let abilities = data.abilities("Annie").unwrap();
+        assert_eq!(abilities[0].as_ref().unwrap().name, "Disintegrate");
+        assert!(abilities[2].is_none());
+        assert!(data.abilities("Garen").unwrap()[1].as_ref().unwrap().damage.is_empty());
+
+        let dagger = data.item(1042).unwrap();
+        assert_eq!(dagger.gold, 250);
+        assert_eq!(dagger.stats[StatKind::AttackSpeed].percent, 12.0);
+        assert!(data.item(3089).unwrap().passive.is_some());
+        assert!(data.item(3089).unwrap().has_rank("legendary"));
+        assert_eq!(data.item(3053).unwrap().unique_passives, ["Lifeline"]);
+        assert_eq!(data.item(3031).unwrap().builds_from, [1036, 1018, 1036]);
+        assert!(!data.item(3600).unwrap().purchasable);
+        assert_eq!(data.item(7050).unwrap().required_champion.as_deref(), Some("Gangplank"));
+    }
+
+    #[test]
+    fn unknown_items_are_errors() {
+        let data = sample();
+        assert_eq!(data.item_sources(&[3089, 0]).unwrap().len(), 2);
+        assert_eq!(data.item_sources(&[9999]).unwrap_err(), "Unknown item 9999");
+    }
+
+    #[test]
+    fn replaced_data_is_seen_by_new_requests() {
+        let shared = SharedGameData::new(GameData::default());
+        let before = shared.get();
+        shared.replace(sample());
+        assert!(before.champion("Garen").is_none());
+        assert!(shared.get().champion("Garen").is_some());
+    }
+}
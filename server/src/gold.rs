@@ -0,0 +1,302 @@
+/*
+ * File: gold.rs
+ *
+ * Copyright (c) 2025 Alan Fung
+ *
+ * Description: values items by what their stats would cost if bought from basic items
+ */
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::gamedata::{GameData, SharedGameData};
+use crate::session::Sessions;
+use crate::stats::{StatKind, StatUnit, Stats};
+
+/// Items that set the gold value of a stat, in order. Items later in the list can also have
+/// stats priced by earlier items, which are subtracted from their cost first.
+const REFERENCES: [(u16, StatKind); 15] = [
+    (1036, StatKind::AttackDamage),         // long sword
+    (1052, StatKind::AbilityPower),         // amplifying tome
+    (1029, StatKind::Armor),                // cloth armor
+    (1033, StatKind::MagicResistance),      // null-magic mantle
+    (1028, StatKind::Health),               // ruby crystal
+    (1027, StatKind::Mana),                 // sapphire crystal
+    (1042, StatKind::AttackSpeed),          // dagger
+    (1018, StatKind::CriticalStrikeChance), // cloak of agility
+    (1001, StatKind::Movespeed),            // boots
+    (1006, StatKind::HealthRegen),          // rejuvenation bead
+    (1004, StatKind::ManaRegen),            // faerie charm
+    (3067, StatKind::AbilityHaste),         // kindlegem
+    (3134, StatKind::Lethality),            // serrated dirk
+    (1053, StatKind::Lifesteal),            // vampiric scepter
+    (3114, StatKind::HealAndShieldPower),   // forbidden idol
+];
+
+/// stores how much gold one point of a stat is worth
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StatValue {
+    pub stat: StatKind,
+    /// whether the value is for the flat or the percent part of the stat
+    pub unit: StatUnit,
+    pub gold: f64,
+    /// name of the item the value was derived from
+    pub reference: String,
+}
+
+/// stores the gold value of every stat that has a reference item in the item data
+#[derive(Debug, Clone, Default)]
+pub struct GoldValues {
+    values: HashMap<(StatKind, StatUnit), StatValue>,
+}
+
+/// stores the gold value of a set of stats
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Valuation {
+    pub gold: f64,
+    /// stats that have no reference item, and so add nothing to the value
+    pub unpriced: Vec<StatKind>,
+}
+
+impl Valuation {
+    fn add(&mut self, other: Valuation) {
+        self.gold += other.gold;
+        for kind in other.unpriced {
+            if !self.unpriced.contains(&kind) {
+                self.unpriced.push(kind);
+            }
+        }
+    }
+}
+
+/// Returns the parts of a stat that an item actually grants, as a unit and an amount.
+fn parts(stats: &Stats, kind: StatKind) -> impl Iterator<Item = (StatUnit, f64)> {
+    let stat = stats[kind];
+    [(StatUnit::Flat, stat.flat), (StatUnit::Percent, stat.percent)]
+        .into_iter()
+        .filter(|&(_, amount)| amount != 0.0)
+}
+
+impl GoldValues {
+    /// Derives stat values from the prices of the reference items. Stats whose reference item is
+    /// missing are left without a value.
+    pub fn derive(data: &GameData) -> Self {
+        let mut values = GoldValues::default();
+
+        for (id, kind) in REFERENCES {
+            let Some(item) = data.item(id) else { continue };
+            let Some((unit, amount)) = parts(&item.stats, kind).next() else { continue };
+
+            let mut others = item.stats.clone();
+            others[kind] = Default::default();
+            let remaining = item.gold as f64 - values.value(&others).gold;
+
+            values.values.insert((kind, unit), StatValue {
+                stat: kind,
+                unit,
+                gold: remaining / amount,
+                reference: item.name.clone(),
+            });
+        }
+
+        values
+    }
+
+    /// Lists every stat value.
+    pub fn list(&self) -> Vec<StatValue> {
+        let mut values: Vec<StatValue> = self.values.values().cloned().collect();
+        values.sort_by_key(|value| (value.stat, value.unit == StatUnit::Percent));
+        values
+    }
+
+    /// Values a set of stats.
+    pub fn value(&self, stats: &Stats) -> Valuation {
+        let mut valuation = Valuation::default();
+        for kind in StatKind::ALL {
+            for (unit, amount) in parts(stats, kind) {
+                match self.values.get(&(kind, unit)) {
+                    Some(value) => valuation.gold += amount * value.gold,
+                    None => valuation.add(Valuation { gold: 0.0, unpriced: vec![kind] }),
+                }
+            }
+        }
+        valuation
+    }
+
+    /// Values a passive. Passives that increase a stat by a percent, like Rabadon's Deathcap,
+    /// are worth that percent of the flat stat they scale.
+    pub fn passive_value(&self, passive: &Stats, scaled: &Stats) -> Valuation {
+        let mut converted = passive.clone();
+        for kind in StatKind::ALL.into_iter().filter(|kind| kind.percent_scales_total()) {
+            if converted[kind].percent != 0.0 && !self.values.contains_key(&(kind, StatUnit::Percent)) {
+                converted[kind].flat += converted[kind].percent / 100.0 * scaled[kind].flat;
+                converted[kind].percent = 0.0;
+            }
+        }
+        self.value(&converted)
+    }
+}
+
+/// stores how much an item's stats are worth compared to its price
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GoldEfficiency {
+    pub id: u16,
+    pub name: String,
+    pub cost: u32,
+    /// value of the listed stats
+    pub value: f64,
+    /// value of the listed stats and the passive
+    pub value_with_passive: f64,
+    /// value as a percent of the cost
+    pub efficiency: f64,
+    pub efficiency_with_passive: f64,
+    pub unpriced: Vec<StatKind>,
+}
+
+/// Turns a value into a percent of a cost. Free items are shown as 0% efficient.
+fn efficiency(value: f64, cost: u32) -> f64 {
+    if cost == 0 { 0.0 } else { value / cost as f64 * 100.0 }
+}
+
+/// Works out the gold efficiency of an item. Percent passives scale the given stats, which
+/// are the item's own stats when looking at an item alone.
+pub fn item_efficiency(data: &GameData, values: &GoldValues, id: u16, scaled: Option<&Stats>) -> Result<GoldEfficiency, String> {
+    let item = data.item(id).ok_or_else(|| format!("Unknown item {}", id))?;
+
+    let mut valuation = values.value(&item.stats);
+    let value = valuation.gold;
+    if let Some(passive) = &item.passive {
+        valuation.add(values.passive_value(passive, scaled.unwrap_or(&item.stats)));
+    }
+
+    Ok(GoldEfficiency {
+        id,
+        name: item.name.clone(),
+        cost: item.gold,
+        value,
+        value_with_passive: valuation.gold,
+        efficiency: efficiency(value, item.gold),
+        efficiency_with_passive: efficiency(valuation.gold, item.gold),
+        unpriced: valuation.unpriced,
+    })
+}
+
+/// stores the gold efficiency of every item in an inventory and of the inventory as a whole
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryEfficiency {
+    pub items: Vec<GoldEfficiency>,
+    pub cost: u32,
+    pub value: f64,
+    pub value_with_passive: f64,
+    pub efficiency: f64,
+    pub efficiency_with_passive: f64,
+}
+
+/// Works out the gold efficiency of an inventory. Percent passives scale the stats of every item
+/// in the inventory, so Rabadon's Deathcap is worth more alongside other ability power items.
+pub fn inventory_efficiency(data: &GameData, values: &GoldValues, items: &[u16]) -> Result<InventoryEfficiency, String> {
+    let mut total = Stats::new();
+    for &id in items.iter().filter(|&&id| id != 0) {
+        total += &data.item(id).ok_or_else(|| format!("Unknown item {}", id))?.stats;
+    }
+
+    let items = items.iter()
+        .filter(|&&id| id != 0)
+        .map(|&id| item_efficiency(data, values, id, Some(&total)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let cost = items.iter().map(|item| item.cost).sum();
+    let value = items.iter().map(|item| item.value).sum();
+    let value_with_passive = items.iter().map(|item| item.value_with_passive).sum();
+
+    Ok(InventoryEfficiency {
+        items,
+        cost,
+        value,
+        value_with_passive,
+        efficiency: efficiency(value, cost),
+        efficiency_with_passive: efficiency(value_with_passive, cost),
+    })
+}
+
+/// Lists the gold value of every stat that can be priced.
+pub async fn get_gold_values(game_data: web::Data<SharedGameData>) -> impl actix_web::Responder {
+    HttpResponse::Ok().json(GoldValues::derive(&game_data.get()).list())
+}
+
+/// Shows the gold efficiency of a given item.
+pub async fn get_item_efficiency(game_data: web::Data<SharedGameData>, id: web::Path<u16>) -> impl actix_web::Responder {
+    let data = game_data.get();
+    match item_efficiency(&data, &GoldValues::derive(&data), id.into_inner(), None) {
+        Ok(efficiency) => HttpResponse::Ok().json(efficiency),
+        Err(err) => HttpResponse::NotFound().body(err),
+    }
+}
+
+/// Shows the gold efficiency of the player's inventory.
+pub async fn get_player_efficiency(req: HttpRequest, sessions: web::Data<Sessions>, game_data: web::Data<SharedGameData>) -> impl actix_web::Responder {
+    let session = sessions.for_request(&req).await;
+    let session = session.lock().await;
+    let data = game_data.get();
+
+    match inventory_efficiency(&data, &GoldValues::derive(&data), &session.player.items) {
+        Ok(efficiency) => HttpResponse::Ok().json(efficiency),
+        Err(err) => HttpResponse::BadRequest().body(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamedata::tests::sample;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn values_come_from_basic_items() {
+        let data = sample();
+        let values = GoldValues::derive(&data);
+        let gold = |kind: StatKind, unit: StatUnit| values.values[&(kind, unit)].gold;
+
+        assert!(close(gold(StatKind::AttackDamage, StatUnit::Flat), 35.0));
+        assert!(close(gold(StatKind::Armor, StatUnit::Flat), 20.0));
+        assert!(close(gold(StatKind::AttackSpeed, StatUnit::Percent), 250.0 / 12.0));
+        assert!(close(gold(StatKind::CriticalStrikeChance, StatUnit::Percent), 40.0));
+        assert!(!values.values.contains_key(&(StatKind::Mana, StatUnit::Flat)));
+    }
+
+    #[test]
+    fn items_with_passives() {
+        let data = sample();
+        let values = GoldValues::derive(&data);
+
+        let infinity_edge = item_efficiency(&data, &values, 3031, None).unwrap();
+        assert!(close(infinity_edge.value, 65.0 * 35.0 + 25.0 * 40.0));
+        assert!(close(infinity_edge.efficiency, 3275.0 / 3450.0 * 100.0));
+        assert_eq!(infinity_edge.unpriced, [StatKind::CriticalStrikeDamage]);
+
+        let rabadon = item_efficiency(&data, &values, 3089, None).unwrap();
+        assert!(close(rabadon.value, 130.0 * 20.0));
+        assert!(close(rabadon.value_with_passive, 169.0 * 20.0));
+        assert!(item_efficiency(&data, &values, 9999, None).is_err());
+    }
+
+    #[test]
+    fn inventory_passives_scale_every_item() {
+        let data = sample();
+        let values = GoldValues::derive(&data);
+        let inventory = inventory_efficiency(&data, &values, &[3089, 1052, 0, 0, 0, 0]).unwrap();
+
+        assert_eq!(inventory.items.len(), 2);
+        assert_eq!(inventory.cost, 4000);
+        assert!(close(inventory.value, 150.0 * 20.0));
+        assert!(close(inventory.value_with_passive, 195.0 * 20.0));
+        assert!(close(inventory.efficiency_with_passive, 3900.0 / 4000.0 * 100.0));
+    }
+}
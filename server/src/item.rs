@@ -6,19 +6,19 @@
  * Description: collection of utility functions dealing with items
  */
 use actix_web::{web, HttpResponse, Responder};
-use std::collections::HashMap;
 use scraper::{Html, Selector};
 use reqwest::Client;
 use serde_json::Value;
 use std::fs::{self, File};
-use std::io::{Write, Read};
+use std::io::Write;
 use std::path::Path;
 
-use crate::stats::Stats;
+use crate::breakdown::{Source, SourceKind};
+use crate::stats::{StatKind, Stats};
 
 const ITEM_URL: &str = "https://cdn.merakianalytics.com/riot/lol/resources/latest/en-US/items.json";
 const ITEM_ICON_URL: &str = "https://raw.communitydragon.org/latest/plugins/rcp-be-lol-game-data/global/default/assets/items/icons2d";
-const ITEM_CACHE_PATH: &str = "public/items_cache.json"; 
+pub const ITEM_CACHE_PATH: &str = "public/items_cache.json";
 const ITEM_ICON_CACHE_PATH: &str = "public/item_icons"; 
 
 /// Checks if item data is cached. If not, creates the cache.
@@ -148,7 +148,7 @@ pub async fn ensure_item_icon_cache() -> Result<(), String> {
 
                 //println!("Downloading file: {}", file_url);
 
-                let file_name = file.split('/').last().unwrap_or("unknown.png");
+                let file_name = file.split('/').next_back().unwrap_or("unknown.png");
                 let file_path = format!("{}/{}", ITEM_ICON_CACHE_PATH, file_name);
 
                 if Path::new(&file_path).exists() {
@@ -246,7 +246,7 @@ pub async fn update_item_icon_cache() -> Result<(), String> {
 
             println!("Downloading file: {}", file_url);
 
-            let file_name = file.split('/').last().unwrap_or("unknown.png");
+            let file_name = file.split('/').next_back().unwrap_or("unknown.png");
             let file_path = format!("{}/{}", ITEM_ICON_CACHE_PATH, file_name);
 
             if Path::new(&file_path).exists() {
@@ -362,34 +362,128 @@ pub async fn get_item_id(name: web::Path<String>) -> impl Responder {
 }
 
 
-/// Retrieves the stats of a given item.
-pub async fn get_item_stats(id: u16) -> Option<Stats> {
-    let mut file = File::open(ITEM_CACHE_PATH).expect("Unable to open file");
-    let mut data = String::new();
-    ensure_item_cache().await.expect("Failed to ensure item cache");
-    file.read_to_string(&mut data).expect("Unable to read file");
+/// stores an item's stats, keeping the effects of its passive apart from its listed stats
+#[derive(Debug, Clone)]
+pub struct ItemStats {
+    pub name: String,
+    /// total cost in the shop, including components
+    pub gold: u32,
+    /// ranks such as BASIC, LEGENDARY or BOOTS
+    pub rank: Vec<String>,
+    /// whether the item can be bought in the shop right now
+    pub purchasable: bool,
+    /// champion the item is limited to, such as Gangplank's upgrades
+    pub required_champion: Option<String>,
+    /// names of unique passives, of which a build can only have one each
+    pub unique_passives: Vec<String>,
+    /// ids of the items this item is built from
+    pub builds_from: Vec<u16>,
+    pub stats: Stats,
+    pub passive: Option<Stats>,
+}
 
-    let items: HashMap<String, HashMap<String, serde_json::Value>> = 
-        serde_json::from_str(&data).expect("JSON poorly formatted");
+/// Reads the name, cost and stats of an item from its entry in the item data.
+pub fn parse_item(id: u16, item: &Value) -> Option<ItemStats> {
+    let mut stats: Stats = serde_json::from_value(item.get("stats")?.clone()).ok()?;
 
-    items.values()
-        .find(|item| item.get("id").and_then(|v| v.as_u64()).map(|v| v as u16) == Some(id))
-        .and_then(|item| {
-            let mut stats: Stats = serde_json::from_value(item.get("stats")?.clone()).ok()?;
+    // item attack speed is always bonus attack speed, which is stored as a percent
+    let attack_speed_stat = &mut stats[StatKind::AttackSpeed];
+    if attack_speed_stat.flat != 0.0 { 
+        attack_speed_stat.percent += attack_speed_stat.flat; 
+        attack_speed_stat.flat = 0.0; 
+    }
 
-            // stupid attack speed workaround
-            let attack_speed_stat = &mut stats.attack_speed;
-            if attack_speed_stat.flat != 0.0 { 
-                attack_speed_stat.percent += attack_speed_stat.flat; 
-                attack_speed_stat.flat = 0.0; 
-            }
+    // percent magic penetration is kept apart from flat magic penetration
+    let magic_penetration_stat = &mut stats[StatKind::MagicPenetration];
+    if magic_penetration_stat.percent != 0.0 {
+        stats[StatKind::MagicPenetrationPercent].percent += magic_penetration_stat.percent;
+        stats[StatKind::MagicPenetration].percent = 0.0;
+    }
 
-            // rabadon's deathcap
-            if id == 3089 {
-                let ability_power_stat = &mut stats.ability_power;
-                ability_power_stat.percent += 30.0;
-            }
+    let name = item.get("name").and_then(|v| v.as_str()).unwrap_or("unknown").to_string();
+    let gold = item.get("shop")
+        .and_then(|shop| shop.pointer("/prices/total"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u32;
+    let rank = item.get("rank")
+        .and_then(|v| v.as_array())
+        .map(|ranks| ranks.iter().filter_map(|rank| rank.as_str()).map(str::to_string).collect())
+        .unwrap_or_default();
+    let removed = item.get("removed").and_then(|v| v.as_bool()).unwrap_or(false);
+    let purchasable = !removed && item.get("shop")
+        .and_then(|shop| shop.get("purchasable"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let required_champion = item.get("requiredChampion")
+        .and_then(|v| v.as_str())
+        .filter(|champion| !champion.is_empty())
+        .map(str::to_string);
+    let unique_passives = item.get("passives")
+        .and_then(|v| v.as_array())
+        .map(|passives| passives.iter()
+            .filter(|passive| passive.get("unique").and_then(|v| v.as_bool()).unwrap_or(false))
+            .filter_map(|passive| passive.get("name").and_then(|v| v.as_str()))
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect())
+        .unwrap_or_default();
+    let builds_from = item.get("buildsFrom")
+        .and_then(|v| v.as_array())
+        .map(|components| components.iter().filter_map(|component| component.as_u64()).map(|id| id as u16).collect())
+        .unwrap_or_default();
+
+    Some(ItemStats {
+        name,
+        gold,
+        rank,
+        purchasable,
+        required_champion,
+        unique_passives,
+        builds_from,
+        stats,
+        passive: passive_stats(id),
+    })
+}
+
+impl ItemStats {
+    /// Checks whether the item has a given rank, such as LEGENDARY.
+    pub fn has_rank(&self, rank: &str) -> bool {
+        self.rank.iter().any(|r| r.eq_ignore_ascii_case(rank))
+    }
+
+    /// Lists the item and its passive as stat sources.
+    pub fn sources(&self, id: u16) -> Vec<Source> {
+        let mut sources = vec![Source {
+            kind: SourceKind::Item,
+            id: id.to_string(),
+            name: self.name.clone(),
+            stats: self.stats.clone(),
+        }];
+        if let Some(passive) = &self.passive {
+            sources.push(Source {
+                kind: SourceKind::Passive,
+                id: id.to_string(),
+                name: format!("{} passive", self.name),
+                stats: passive.clone(),
+            });
+        }
+        sources
+    }
+}
+
+/// Returns the stats granted by an item's passive that are missing from the item data.
+fn passive_stats(id: u16) -> Option<Stats> {
+    let mut stats = Stats::new();
+
+    match id {
+        // rabadon's deathcap
+        3089 => stats[StatKind::AbilityPower].percent += 30.0,
+        // infinity edge
+        3031 => stats[StatKind::CriticalStrikeDamage].flat += 40.0,
+        // boots of swiftness
+        3009 => stats[StatKind::SlowResistance].percent += 25.0,
+        _ => return None,
+    }
 
-            Some(stats)
-        })
+    Some(stats)
 }
@@ -8,19 +8,44 @@
 use actix_web::{web, App, HttpServer};
 use actix_cors::Cors;
 use actix_web::http::header;
-use tokio::sync::Mutex;
+use actix_web::middleware::from_fn;
+mod ability;
+mod breakdown;
+mod buildcode;
 mod champion;
+mod combat;
+mod combo;
+mod damage;
+mod duel;
+mod evaluate;
+mod gamedata;
+mod gold;
 mod item;
+mod matchup;
+mod optimizer;
 mod stats;
 mod player;
+mod registry;
+mod session;
+mod skills;
+mod storage;
+mod timeline;
 
-use crate::player::Player;
+use crate::gamedata::{GameData, SharedGameData};
+use crate::matchup::MatrixCache;
+use crate::session::Sessions;
+use crate::storage::BuildStore;
 
-pub async fn update_caches() -> impl actix_web::Responder {
+/// batches of builds can be much larger than other request bodies
+const BATCH_PAYLOAD_LIMIT: usize = 32 * 1024 * 1024;
+
+pub async fn update_caches(game_data: web::Data<SharedGameData>, matrices: web::Data<MatrixCache>) -> impl actix_web::Responder {
     champion::update_champ_cache().await.expect("Failed to update champion cache");
     champion::update_champ_icon_cache().await.expect("Failed to update champion icon cache");
     item::update_item_cache().await.expect("Failed to update item cache");
     item::update_item_icon_cache().await.expect("Failed to update item icon cache");
+    game_data.replace(GameData::load().expect("Failed to load game data"));
+    matrices.clear();
 
     actix_web::HttpResponse::Ok()
 }
@@ -32,11 +57,18 @@ async fn main() -> std::io::Result<()> {
     item::ensure_item_cache().await.expect("Failed to ensure item cache");
     item::ensure_item_icon_cache().await.expect("Failed to ensure item icon cache");
 
-    let player = web::Data::new(Mutex::new(Player::new()));
+    let game_data = web::Data::new(SharedGameData::new(GameData::load().expect("Failed to load game data")));
+    let sessions = web::Data::new(Sessions::new());
+    let builds = web::Data::new(BuildStore::open_default().expect("Failed to open build storage"));
+    let matrices = web::Data::new(MatrixCache::new());
 
     HttpServer::new(move || {
         App::new()
-            .app_data(player.clone())
+            .app_data(game_data.clone())
+            .app_data(sessions.clone())
+            .app_data(builds.clone())
+            .app_data(matrices.clone())
+            .wrap(from_fn(session::issue_session_cookie))
             .wrap(
                 Cors::default()
                     .allowed_origin("http://localhost:5173")
@@ -45,23 +77,64 @@ async fn main() -> std::io::Result<()> {
                         header::CONTENT_TYPE,
                         header::ACCEPT,
                     ])
+                    .supports_credentials()
                     .max_age(3600),
             )
+            .route("/sessions", web::post().to(session::create_session))
+            .route("/sessions", web::delete().to(session::delete_session))
             .route("/updatecaches", web::post().to(update_caches))
             .route("/getchampion", web::get().to(champion::get_current_champion))
             .route("/champion", web::get().to(champion::fetch_champs))
+            .route("/growth/{champion}", web::get().to(player::champion_growth))
             .route("/champion/{name}", web::get().to(champion::get_champion))
             .route("/champion/{name}/{property:.*}", web::get().to(champion::get_champion_property_nested))
             .route("/setchampion/{champion_name}", web::post().to(champion::set_champion))
             .route("/item", web::get().to(item::fetch_items))
             .route("/item/{name}", web::get().to(item::get_item))
+            .route("/item/{id}/goldefficiency", web::get().to(gold::get_item_efficiency))
+            .route("/goldvalues", web::get().to(gold::get_gold_values))
+            .route("/stats", web::get().to(registry::get_stat_registry))
             .route("/player", web::get().to(player::get_player))
             .route("/displayplayerstats", web::get().to(player::display_stats))
+            .route("/player/undo", web::post().to(session::undo))
+            .route("/player/redo", web::post().to(session::redo))
+            .route("/player/history", web::get().to(session::get_history))
+            .route("/player/goldefficiency", web::get().to(gold::get_player_efficiency))
+            .route("/player/stats/breakdown", web::get().to(player::stats_breakdown))
+            .route("/player/growth", web::get().to(player::player_growth))
+            .route("/player/damage", web::post().to(damage::player_damage))
+            .route("/player/dps", web::post().to(combat::player_dps))
+            .route("/player/combo", web::post().to(combo::player_combo))
+            .route("/player/effectivehealth", web::post().to(damage::player_effective_health))
+            .route("/evaluate", web::post().to(evaluate::evaluate_build))
+            .service(
+                web::resource("/evaluate/batch")
+                    .app_data(web::PayloadConfig::new(BATCH_PAYLOAD_LIMIT))
+                    .route(web::post().to(evaluate::evaluate_builds)),
+            )
+            .route("/optimize", web::post().to(optimizer::optimize_build))
+            .route("/dps", web::post().to(combat::build_dps))
+            .route("/combo", web::post().to(combo::build_combo))
+            .route("/duel", web::post().to(duel::simulate_duel))
+            .route("/matchups", web::post().to(matchup::get_matchups))
+            .route("/timeline", web::post().to(timeline::game_timeline))
+            .route("/compare", web::post().to(evaluate::compare_builds))
             .route("/getitemid/{name}", web::get().to(item::get_item_id))
             .route("/additem/{id}", web::post().to(player::add_item))
             .route("/removelastitem", web::post().to(player::remove_last_item))
             .route("/setitem/{item}/{id}", web::post().to(player::set_item))
             .route("/changeskillpoint/{ability}/{updown}", web::post().to(player::change_skill_point))
+            .route("/setlevel/{level}", web::post().to(player::set_level))
+            .route("/levelup", web::post().to(player::level_up))
+            .route("/leveldown", web::post().to(player::level_down))
+            .route("/build/export", web::get().to(buildcode::export_build))
+            .route("/build/import/{code}", web::post().to(buildcode::import_build))
+            .route("/builds", web::get().to(storage::list_builds))
+            .route("/builds/{name}", web::post().to(storage::save_build))
+            .route("/builds/{name}", web::delete().to(storage::delete_build))
+            .route("/builds/{name}/load", web::post().to(storage::load_build))
+            .route("/builds/{name}/rename/{new_name}", web::post().to(storage::rename_build))
+            .route("/builds/{name}/duplicate/{new_name}", web::post().to(storage::duplicate_build))
     })
     .bind("127.0.0.1:8080")?
     .run()
@@ -0,0 +1,312 @@
+/*
+ * File: matchup.rs
+ *
+ * Copyright (c) 2025 Alan Fung
+ *
+ * Description: compares every pair of champions with a template build each, either by duelling
+ * them or by the damage of a full combo
+ */
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::combat::Attacker;
+use crate::combo;
+use crate::damage::TargetStats;
+use crate::duel::{self, Side};
+use crate::evaluate::Build;
+use crate::gamedata::{GameData, SharedGameData};
+use crate::skills;
+
+/// most matrices kept in the cache before it is cleared
+const CACHE_LIMIT: usize = 32;
+
+/// what each cell of a matchup matrix measures
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Metric {
+    /// seconds the row champion takes to win a duel against the column champion
+    TimeToKill,
+    /// percent of the column champion's health taken by one full combo from the row champion
+    Burst,
+}
+
+/// describes a matchup matrix
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MatrixRequest {
+    pub level: u8,
+    pub metric: Metric,
+    /// champions to compare, or every champion when missing
+    #[serde(default)]
+    pub champions: Option<Vec<String>>,
+    /// items of each champion's template build, where champions without one have no items
+    #[serde(default)]
+    pub templates: BTreeMap<String, Vec<u16>>,
+    /// seconds a duel can last before it is called a draw
+    #[serde(default = "default_duration")]
+    pub duration: f64,
+}
+
+fn default_duration() -> f64 {
+    30.0
+}
+
+/// stores a matchup matrix, where each row champion is measured against each column champion
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchupMatrix {
+    /// patch of the data the matrix was computed from
+    pub patch: String,
+    pub level: u8,
+    pub metric: Metric,
+    pub champions: Vec<String>,
+    /// a value for each row and column, or None where the row champion does not win the duel
+    pub values: Vec<Vec<Option<f64>>>,
+}
+
+impl MatrixRequest {
+    /// Builds the template build of a champion, with skill points spent the usual way.
+    fn template(&self, champion: &str) -> Build {
+        Build {
+            champion: champion.to_string(),
+            level: self.level,
+            items: self.templates.get(champion).cloned().unwrap_or_default(),
+            skill_points: skills::default_skill_points(champion, self.level),
+        }
+    }
+
+    /// Lists the champions to compare in a stable order, rejecting unknown ones.
+    fn champions(&self, data: &GameData) -> Result<Vec<String>, String> {
+        let mut champions: Vec<String> = match &self.champions {
+            Some(champions) => champions.clone(),
+            None => data.champion_names().map(str::to_string).collect(),
+        };
+        champions.sort();
+        champions.dedup();
+
+        if let Some(unknown) = champions.iter().find(|champion| data.champion(champion).is_none()) {
+            return Err(format!("Unknown champion {}", unknown));
+        }
+        Ok(champions)
+    }
+}
+
+/// Measures the percent of a target's health taken by a full combo.
+fn burst(data: &GameData, attacker: &Build, target: &TargetStats) -> Result<f64, String> {
+    Ok(100.0 - combo::full_combo(data, attacker, target)?.target_health_percent)
+}
+
+/// Computes the value of every cell in a matchup matrix, spread across every available thread.
+pub fn matrix(data: &GameData, request: &MatrixRequest, patch: String) -> Result<MatchupMatrix, String> {
+    let champions = request.champions(data)?;
+    let builds: Vec<Build> = champions.iter().map(|champion| request.template(champion)).collect();
+
+    let mut targets = Vec::new();
+    for build in &builds {
+        let (sources, _) = build.sources(data)?;
+        let attacker = Attacker::from_sources(&sources, build.level, &build.champion);
+        targets.push(TargetStats::from_resolved(&attacker.stats));
+    }
+
+    let cell = |row: &Build, column: usize| -> Result<Option<f64>, String> {
+        match request.metric {
+            Metric::TimeToKill => {
+                let result = duel::duel(data, row, &builds[column], request.duration)?;
+                Ok(result.time_to_kill.filter(|_| result.winner == Some(Side::First)))
+            }
+            Metric::Burst => burst(data, row, &targets[column]).map(Some),
+        }
+    };
+
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let chunk_size = builds.len().div_ceil(threads).max(1);
+    let values = thread::scope(|scope| {
+        let chunks: Vec<_> = builds.chunks(chunk_size)
+            .map(|chunk| scope.spawn(|| {
+                chunk.iter()
+                    .map(|row| (0..builds.len()).map(|column| cell(row, column)).collect::<Result<Vec<_>, _>>())
+                    .collect::<Result<Vec<_>, _>>()
+            }))
+            .collect();
+
+        chunks.into_iter()
+            .map(|chunk| chunk.join().expect("Matchup matrix panicked"))
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+
+    Ok(MatchupMatrix {
+        patch,
+        level: request.level,
+        metric: request.metric,
+        champions,
+        values: values.into_iter().flatten().collect(),
+    })
+}
+
+/// Quotes a CSV field if it needs it.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+impl MatchupMatrix {
+    /// Writes the matrix as CSV, with a header row of column champions and empty cells where
+    /// there is no value.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        for champion in &self.champions {
+            csv.push(',');
+            csv.push_str(&csv_field(champion));
+        }
+        csv.push('\n');
+
+        for (champion, row) in self.champions.iter().zip(&self.values) {
+            csv.push_str(&csv_field(champion));
+            for value in row {
+                csv.push(',');
+                if let Some(value) = value {
+                    csv.push_str(&format!("{:.3}", value));
+                }
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// stores computed matrices by patch and request, since computing one takes a while
+#[derive(Default)]
+pub struct MatrixCache {
+    matrices: Mutex<HashMap<String, Arc<MatchupMatrix>>>,
+}
+
+impl MatrixCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Retrieves a cached matrix, or computes and caches it. Matrices are keyed by the patch of
+    /// the game data they are computed from, and requests that list the same champions in a
+    /// different order share a matrix.
+    pub fn get_or_compute(&self, data: &GameData, request: &MatrixRequest) -> Result<Arc<MatchupMatrix>, String> {
+        let patch = data.patch().unwrap_or("unknown").to_string();
+        let mut normalized = request.clone();
+        if let Some(champions) = &mut normalized.champions {
+            champions.sort();
+            champions.dedup();
+        }
+        let key = format!("{}:{}", patch, serde_json::to_string(&normalized).map_err(|e| e.to_string())?);
+        if let Some(matrix) = self.matrices.lock().expect("Matrix cache poisoned").get(&key) {
+            return Ok(matrix.clone());
+        }
+
+        let matrix = Arc::new(matrix(data, request, patch)?);
+        let mut matrices = self.matrices.lock().expect("Matrix cache poisoned");
+        if matrices.len() >= CACHE_LIMIT {
+            matrices.clear();
+        }
+        matrices.insert(key, matrix.clone());
+        Ok(matrix)
+    }
+
+    /// Forgets every cached matrix, for when the game data changes.
+    pub fn clear(&self) {
+        self.matrices.lock().expect("Matrix cache poisoned").clear();
+    }
+}
+
+/// This structure is only useful for reading the query of get_matchups.
+#[derive(Deserialize)]
+pub struct ExportFormat {
+    /// "json" or "csv", defaulting to JSON
+    #[serde(default)]
+    format: Option<String>,
+}
+
+/// Computes a matchup matrix for the champions and template builds in the body, returning it as
+/// JSON or CSV.
+pub async fn get_matchups(game_data: web::Data<SharedGameData>, cache: web::Data<MatrixCache>, query: web::Query<ExportFormat>, body: web::Json<MatrixRequest>) -> impl actix_web::Responder {
+    let csv = match query.format.as_deref() {
+        None | Some("json") => false,
+        Some("csv") => true,
+        Some(other) => return HttpResponse::BadRequest().body(format!("Unknown format {}", other)),
+    };
+
+    let request = body.into_inner();
+    let data = game_data.get();
+    match web::block(move || cache.get_or_compute(&data, &request)).await {
+        Ok(Ok(matrix)) if csv => HttpResponse::Ok().content_type("text/csv").body(matrix.to_csv()),
+        Ok(Ok(matrix)) => HttpResponse::Ok().json(&*matrix),
+        Ok(Err(err)) => HttpResponse::BadRequest().body(err),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamedata::tests::sample;
+
+    fn request(metric: Metric) -> MatrixRequest {
+        MatrixRequest {
+            level: 18,
+            metric,
+            champions: None,
+            templates: BTreeMap::from([("Garen".to_string(), vec![3031, 3072, 3046])]),
+            duration: 30.0,
+        }
+    }
+
+    #[test]
+    fn duels_fill_the_matrix() {
+        let matrix = matrix(&sample(), &request(Metric::TimeToKill), "test".to_string()).unwrap();
+        assert_eq!(matrix.champions, ["Annie", "Garen"]);
+        assert_eq!(matrix.values[0][0], None);
+        assert_eq!(matrix.values[0][1], None);
+        assert!(matrix.values[1][0].is_some());
+    }
+
+    #[test]
+    fn burst_is_a_percent_of_health() {
+        let matrix = matrix(&sample(), &request(Metric::Burst), "test".to_string()).unwrap();
+        for row in &matrix.values {
+            for value in row {
+                let value = value.unwrap();
+                assert!((0.0..=100.0).contains(&value));
+            }
+        }
+        assert!(matrix.values[1][0] > matrix.values[0][1]);
+    }
+
+    #[test]
+    fn unknown_champions_are_errors() {
+        let request = MatrixRequest { champions: Some(vec!["Teemo".to_string()]), ..request(Metric::Burst) };
+        assert_eq!(matrix(&sample(), &request, "test".to_string()).unwrap_err(), "Unknown champion Teemo");
+    }
+
+    #[test]
+    fn matrices_export_as_csv_and_are_cached() {
+        let data = sample();
+        let cache = MatrixCache::new();
+        let request = MatrixRequest { champions: Some(vec!["Garen".to_string(), "Annie".to_string()]), ..request(Metric::TimeToKill) };
+
+        let matrix = cache.get_or_compute(&data, &request).unwrap();
+        assert!(Arc::ptr_eq(&matrix, &cache.get_or_compute(&data, &request).unwrap()));
+        let reordered = MatrixRequest { champions: Some(vec!["Annie".to_string(), "Garen".to_string(), "Annie".to_string()]), ..request.clone() };
+        assert!(Arc::ptr_eq(&matrix, &cache.get_or_compute(&data, &reordered).unwrap()));
+        assert_eq!(matrix.patch, "14.10");
+
+        let csv = matrix.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], ",Annie,Garen");
+        assert_eq!(lines[1], "Annie,,");
+        assert!(lines[2].starts_with("Garen,") && lines[2].ends_with(','));
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+    }
+}
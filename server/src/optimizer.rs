@@ -0,0 +1,302 @@
+/*
+ * File: optimizer.rs
+ *
+ * Copyright (c) 2025 Alan Fung
+ *
+ * Description: searches the item data for the builds that do best at a given objective
+ */
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::combat::{self, Attacker};
+use crate::combo;
+use crate::damage::{Dummy, Reductions, Target, TargetStats};
+use crate::evaluate::{evaluate_batch, BatchEntry, Build, Evaluation};
+use crate::gamedata::{GameData, SharedGameData};
+use crate::item::ItemStats;
+use crate::skills;
+
+/// partial builds kept after each item is added
+const BEAM_WIDTH: usize = 64;
+/// most builds that can be returned
+const MAX_TOP: usize = 50;
+/// most items a build can hold
+const SLOTS: usize = 6;
+
+/// what the optimizer tries to make as large as possible
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Objective {
+    /// auto attack damage per second against the target, on-hit effects included
+    Dps,
+    /// effective health against damage split evenly between physical and magic
+    EffectiveHealth,
+    /// damage of a full combo against the target: every ranked ability with damage, then an auto
+    /// attack
+    Burst,
+}
+
+/// seconds of attacks simulated when scoring damage per second
+const DPS_DURATION: f64 = 10.0;
+
+impl Objective {
+    /// Scores an evaluated build against a target, where higher is better.
+    pub fn score(&self, data: &GameData, evaluation: &Evaluation, target: &TargetStats) -> f64 {
+        match self {
+            Objective::Dps => {
+                let build = &evaluation.build;
+                match build.sources(data) {
+                    Ok((sources, _)) => {
+                        let attacker = Attacker::from_sources(&sources, build.level, &build.champion);
+                        combat::simulate(&attacker, target, &Reductions::default(), DPS_DURATION).dps
+                    }
+                    Err(_) => 0.0,
+                }
+            }
+            Objective::EffectiveHealth => evaluation.effective_health.mixed,
+            Objective::Burst => combo::full_combo(data, &evaluation.build, target).map_or(0.0, |result| result.damage),
+        }
+    }
+}
+
+/// stores a build found by the optimizer along with its score
+#[derive(Serialize, Debug, Clone)]
+pub struct ScoredBuild {
+    pub score: f64,
+    #[serde(flatten)]
+    pub evaluation: Evaluation,
+}
+
+/// Lists the items the optimizer may add: completed items and boots the champion can buy that fit
+/// in the budget.
+fn candidates(data: &GameData, champion: &str, budget: u32) -> Vec<u16> {
+    let mut candidates: Vec<u16> = data.items()
+        .filter(|(_, item)| item.purchasable && item.gold <= budget)
+        .filter(|(_, item)| item.has_rank("LEGENDARY") || item.has_rank("BOOTS"))
+        .filter(|(_, item)| item.required_champion.as_deref().is_none_or(|required| required == champion))
+        .map(|(id, _)| id)
+        .collect();
+    candidates.sort_unstable();
+    candidates
+}
+
+/// Checks whether an item can join a set of items: legendary items and boots can only be bought
+/// once, a build can only have one pair of boots, and two items cannot share a unique passive.
+fn can_add(data: &GameData, items: &[u16], id: u16, item: &ItemStats) -> bool {
+    let limited = item.has_rank("LEGENDARY") || item.has_rank("BOOTS");
+    items.iter().filter_map(|&other| Some((other, data.item(other)?))).all(|(other_id, other)| {
+        let duplicate = limited && other_id == id;
+        let second_boots = item.has_rank("BOOTS") && other.has_rank("BOOTS");
+        let shared_passive = item.unique_passives.iter().any(|passive| other.unique_passives.contains(passive));
+        !(duplicate || second_boots || shared_passive)
+    })
+}
+
+/// Searches for the builds that score best at an objective, keeping the fixed items and staying
+/// within the budget. Items are added one at a time, keeping only the best partial builds after
+/// each step, so the search is fast but not exhaustive.
+pub fn optimize(data: &GameData, base: &Build, budget: u32, objective: Objective, target: &TargetStats, top: usize) -> Result<Vec<ScoredBuild>, String> {
+    let reductions = Reductions::default();
+    let items: Vec<u16> = base.items.iter().copied().filter(|&item| item != 0).collect();
+    for (i, &id) in items.iter().enumerate() {
+        let item = data.item(id).ok_or_else(|| format!("Unknown item {}", id))?;
+        if !can_add(data, &items[..i], id, item) {
+            return Err(format!("Fixed item {} breaks the shop's uniqueness rules", item.name));
+        }
+    }
+
+    let base = Build { items, ..base.clone() };
+    let evaluation = base.evaluate(data, target, &reductions)?;
+    if evaluation.gold > budget {
+        return Err(format!("Fixed items cost {} gold, which is over the budget of {}", evaluation.gold, budget));
+    }
+
+    let candidates = candidates(data, &base.champion, budget);
+    let mut seen = HashSet::new();
+    let mut beam = vec![ScoredBuild { score: objective.score(data, &evaluation, target), evaluation }];
+
+    for _ in base.items.len()..SLOTS {
+        let mut next = Vec::new();
+        let mut builds = Vec::new();
+
+        for state in &beam {
+            let mut extended = false;
+            for &id in &candidates {
+                let item = data.item(id).expect("Candidates come from the game data");
+                if state.evaluation.gold + item.gold > budget || !can_add(data, &state.evaluation.build.items, id, item) {
+                    continue;
+                }
+                extended = true;
+
+                let mut items = state.evaluation.build.items.clone();
+                items.push(id);
+                let mut key = items.clone();
+                key.sort_unstable();
+                if seen.insert(key) {
+                    builds.push(Ok(Build { items, ..base.clone() }));
+                }
+            }
+            if !extended {
+                next.push(state.clone());
+            }
+        }
+
+        if builds.is_empty() {
+            break;
+        }
+        for entry in evaluate_batch(data, &builds, target, &reductions) {
+            if let BatchEntry::Ok(evaluation) = entry {
+                next.push(ScoredBuild { score: objective.score(data, &evaluation, target), evaluation: *evaluation });
+            }
+        }
+
+        next.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.evaluation.gold.cmp(&b.evaluation.gold)));
+        next.truncate(BEAM_WIDTH);
+        beam = next;
+    }
+
+    beam.truncate(top.min(MAX_TOP));
+    Ok(beam)
+}
+
+/// This structure is only useful for reading the body of optimize_build.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizeRequest {
+    champion: String,
+    level: u8,
+    /// skill points of the build, which default to the usual order for the level
+    #[serde(default)]
+    skill_points: Option<[u8; 4]>,
+    /// gold that the whole build can cost, fixed items included, or unlimited if missing
+    budget: Option<u32>,
+    /// items the build must keep
+    #[serde(default)]
+    fixed_items: Vec<u16>,
+    objective: Objective,
+    /// target the build's damage is dealt to, which defaults to a dummy without resistances
+    #[serde(default)]
+    target: Option<Target>,
+    /// number of builds to return
+    #[serde(default = "default_top")]
+    top: usize,
+}
+
+fn default_top() -> usize {
+    5
+}
+
+impl OptimizeRequest {
+    /// Builds the starting build from the champion, level, skill points and fixed items.
+    fn base(&self) -> Build {
+        Build {
+            champion: self.champion.clone(),
+            level: self.level,
+            items: self.fixed_items.clone(),
+            skill_points: self.skill_points.unwrap_or_else(|| skills::default_skill_points(&self.champion, self.level)),
+        }
+    }
+}
+
+/// Finds the best builds for an objective, starting from the fixed items.
+pub async fn optimize_build(game_data: web::Data<SharedGameData>, body: web::Json<OptimizeRequest>) -> impl actix_web::Responder {
+    let request = body.into_inner();
+
+    let data = game_data.get();
+    let base = request.base();
+    let target = request.target.unwrap_or_else(|| Target::Dummy(Dummy::default()));
+    let target = match target.resolve(&data) {
+        Ok(target) => target,
+        Err(err) => return HttpResponse::BadRequest().body(err),
+    };
+    let budget = request.budget.unwrap_or(u32::MAX);
+
+    match web::block(move || optimize(&data, &base, budget, request.objective, &target, request.top)).await {
+        Ok(Ok(builds)) => HttpResponse::Ok().json(builds),
+        Ok(Err(err)) => HttpResponse::BadRequest().body(err),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamedata::tests::sample;
+
+    const DUMMY: TargetStats = TargetStats { armor: 0.0, magic_resistance: 0.0, health: 1000.0 };
+
+    fn garen(items: &[u16]) -> Build {
+        Build { champion: "Garen".to_string(), level: 18, items: items.to_vec(), skill_points: [0; 4] }
+    }
+
+    fn sorted(build: &ScoredBuild) -> Vec<u16> {
+        let mut items = build.evaluation.build.items.clone();
+        items.sort_unstable();
+        items
+    }
+
+    #[test]
+    fn builds_follow_the_shop_rules() {
+        let data = sample();
+        let builds = optimize(&data, &garen(&[]), u32::MAX, Objective::EffectiveHealth, &DUMMY, 10).unwrap();
+        assert_eq!(builds.len(), 10);
+
+        for build in &builds {
+            let items = sorted(build);
+            assert_eq!(items.len(), 6);
+            assert!(items.windows(2).all(|pair| pair[0] != pair[1]));
+            assert!(!(items.contains(&3006) && items.contains(&3047)));
+            assert!(!(items.contains(&3053) && items.contains(&3156)));
+            assert!(!items.contains(&3600) && !items.contains(&7050));
+        }
+        assert!(builds.windows(2).all(|pair| pair[0].score >= pair[1].score));
+    }
+
+    #[test]
+    fn objectives_pick_matching_items() {
+        let data = sample();
+        let tank = optimize(&data, &garen(&[]), u32::MAX, Objective::EffectiveHealth, &DUMMY, 1).unwrap();
+        assert!(sorted(&tank[0]).contains(&3075));
+        assert!(sorted(&tank[0]).contains(&3047));
+
+        let damage = optimize(&data, &garen(&[]), u32::MAX, Objective::Dps, &DUMMY, 1).unwrap();
+        for item in [3031, 3046, 3124, 6672] {
+            assert!(sorted(&damage[0]).contains(&item));
+        }
+        assert!(!sorted(&damage[0]).contains(&3075));
+
+        let annie = Build { champion: "Annie".to_string(), skill_points: [5, 5, 0, 3], ..garen(&[]) };
+        let burst = optimize(&data, &annie, u32::MAX, Objective::Burst, &DUMMY, 1).unwrap();
+        assert!(sorted(&burst[0]).contains(&3089));
+        let combo = combo::full_combo(&data, &burst[0].evaluation.build, &DUMMY).unwrap();
+        assert_eq!(burst[0].score, combo.damage);
+    }
+
+    #[test]
+    fn skill_points_default_to_the_usual_order() {
+        let data = sample();
+        let request: OptimizeRequest = serde_json::from_str(r#"{ "champion": "Annie", "level": 11, "objective": "burst" }"#).unwrap();
+        let base = request.base();
+        assert_eq!(base.skill_points, skills::default_skill_points("Annie", 11));
+
+        let burst = optimize(&data, &base, u32::MAX, Objective::Burst, &DUMMY, 1).unwrap();
+        assert!(burst[0].score > 0.0);
+
+        let request: OptimizeRequest = serde_json::from_str(r#"{ "champion": "Annie", "level": 11, "skillPoints": [1, 0, 0, 0], "objective": "burst" }"#).unwrap();
+        assert_eq!(request.base().skill_points, [1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn budget_and_fixed_items_are_kept() {
+        let data = sample();
+        let builds = optimize(&data, &garen(&[3089]), 8000, Objective::Dps, &DUMMY, 5).unwrap();
+        for build in &builds {
+            assert!(build.evaluation.gold <= 8000);
+            assert_eq!(build.evaluation.build.items[0], 3089);
+        }
+
+        assert!(optimize(&data, &garen(&[3089]), 3000, Objective::Dps, &DUMMY, 5).is_err());
+        assert!(optimize(&data, &garen(&[3006, 3047]), u32::MAX, Objective::Dps, &DUMMY, 5).is_err());
+    }
+}
@@ -6,18 +6,27 @@
  * Description: structs, implementations, and utility functions dealing with the local plyaer
  */ 
 use serde::{Deserialize, Serialize};
-use actix_web::{web, HttpResponse};
-use tokio::sync::Mutex;
+use actix_web::{web, HttpRequest, HttpResponse};
+use std::collections::BTreeMap;
 
-use crate::stats::Stats;
-use crate::item::get_item_stats;
+use crate::breakdown::{self, Source, SourceKind, StatBreakdown};
+use crate::damage::{self, EffectiveHealth};
+use crate::gamedata::{GameData, SharedGameData};
+use crate::stats::{self, AttackSpeed, ResolvedStats, StatKind, Stats};
+use crate::session::Sessions;
+use crate::skills;
+
+/// lowest level a player can be
+pub const MIN_LEVEL: u8 = 1;
+/// highest level a player can be
+pub const MAX_LEVEL: u8 = 18;
 
 /// stores player information
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Player {
-    /// level can range from 0 to 18
+    /// level can range from 1 to 18
     pub level: u8,
-    /// skill point cap should be enforced by the frontend
+    /// skill points spent on Q, W, E and R, following the champion's skill rules
     pub skill_points: [u8; 4],
     /// base stats are provided by the champion
     pub base_stats: Stats,
@@ -47,8 +56,36 @@ impl Player {
         }
     }
 
-    pub fn create_player() -> Player {
-        Player::new()
+    /// Lists every source of the player's stats: the champion, stats stored on the player, and
+    /// each item along with its passive. Items missing from the game data are skipped.
+    pub fn sources(&self, data: &GameData) -> Vec<Source> {
+        let mut sources = vec![Source {
+            kind: SourceKind::Champion,
+            id: self.champ.clone(),
+            name: self.champ.clone(),
+            stats: self.base_stats.clone(),
+        }];
+
+        if self.stats != Stats::new() {
+            sources.push(Source {
+                kind: SourceKind::Bonus,
+                id: "player".to_string(),
+                name: "Player".to_string(),
+                stats: self.stats.clone(),
+            });
+        }
+
+        for &item in &self.items {
+            if let Some(item_data) = data.item(item) {
+                sources.extend(item_data.sources(item));
+            }
+        }
+        sources
+    }
+
+    /// Resolves all of the player's stats at the player's level.
+    pub fn resolved_stats(&self, data: &GameData) -> ResolvedStats {
+        breakdown::resolve(&self.sources(data), self.level, &self.champ)
     }
 }
 
@@ -64,19 +101,14 @@ struct PlayerStats {
 
 /// Shows all of the player's stats. Probably doesn't need to be kept, but this is important for
 /// debugging purposes.
-pub async fn get_player(player_data: web::Data<Mutex<Player>>) -> impl actix_web::Responder {
-    let player = player_data.lock().await;
-
-    let mut merged = Stats::add_stats(&player.base_stats, &player.stats);
+pub async fn get_player(req: HttpRequest, sessions: web::Data<Sessions>, game_data: web::Data<SharedGameData>) -> impl actix_web::Responder {
+    let session = sessions.for_request(&req).await;
+    let session = session.lock().await;
+    let player = &session.player;
 
-    for item in player.items {
-        if item != 0 {
-            if let Some(item_stats) = &get_item_stats(item).await {
-                merged = Stats::add_stats(&merged, item_stats);
-            } else { 
-                println!("No item in slot {}", item);
-            }
-        }
+    let mut merged = Stats::new();
+    for source in player.sources(&game_data.get()) {
+        merged += &source.stats;
     }
     let new_stats = PlayerStats {
         champion: player.champ.clone(),
@@ -89,72 +121,126 @@ pub async fn get_player(player_data: web::Data<Mutex<Player>>) -> impl actix_web
 }
 
 /// Adds a given item id to the first vacant spot in the player's inventory.
-pub async fn add_item(player_data: web::Data<Mutex<Player>>, path: web::Path<u16>) -> impl actix_web::Responder {
-    let mut player = player_data.lock().await;
+pub async fn add_item(req: HttpRequest, sessions: web::Data<Sessions>, path: web::Path<u16>) -> impl actix_web::Responder {
     let item_id = path.into_inner();
-
-    match (0..6).find(|&i| player.items[i] == 0) {
-        Some(item) => {
-            player.items[item] = item_id;
-            HttpResponse::Ok().body(format!("Successfully added item {} to player", item_id))
-        }
-        None => {
-            println!("No inventory space.");
-            HttpResponse::InternalServerError().body("Not enough space to add an item!")
+    let session = sessions.for_request(&req).await;
+    let mut session = session.lock().await;
+    session.edit(format!("Add item {}", item_id), |player| {
+        match (0..6).find(|&i| player.items[i] == 0) {
+            Some(item) => {
+                player.items[item] = item_id;
+                HttpResponse::Ok().body(format!("Successfully added item {} to player", item_id))
+            }
+            None => {
+                println!("No inventory space.");
+                HttpResponse::InternalServerError().body("Not enough space to add an item!")
+            }
         }
-    }
+    })
 }
 
 /// Deletes the last available item from the player's inventory.
-pub async fn remove_last_item(player_data: web::Data<Mutex<Player>>) -> impl actix_web::Responder {
-    let mut player = player_data.lock().await;
-
-    match (0..6).rev().find(|&i| player.items[i] != 0) {
-        Some(item) => { player.items[item] = 0;
-            HttpResponse::Ok().body("Successfully removed last item from player")
-        }
-        None => {
-            println!("No items to remove.");
-            HttpResponse::InternalServerError().body("No items to remove!")
+pub async fn remove_last_item(req: HttpRequest, sessions: web::Data<Sessions>) -> impl actix_web::Responder {
+    let session = sessions.for_request(&req).await;
+    let mut session = session.lock().await;
+    session.edit("Remove last item", |player| {
+        match (0..6).rev().find(|&i| player.items[i] != 0) {
+            Some(item) => { player.items[item] = 0;
+                HttpResponse::Ok().body("Successfully removed last item from player")
+            }
+            None => {
+                println!("No items to remove.");
+                HttpResponse::InternalServerError().body("No items to remove!")
+            }
         }
-    }
+    })
 }
 
 /// Sets a given item slot to a given item id.
-pub async fn set_item(player_data: web::Data<Mutex<Player>>, path: web::Path<(usize, u16)>) -> impl actix_web::Responder {
+pub async fn set_item(req: HttpRequest, sessions: web::Data<Sessions>, path: web::Path<(usize, u16)>) -> impl actix_web::Responder {
     let (item, item_id) = path.into_inner();
     if item > 5 {
         return HttpResponse::InternalServerError().body("Item index out of bounds");
     }
-    let mut player = player_data.lock().await;
-
-    player.items[item] = item_id;
-    HttpResponse::Ok().body(format!("Successfully set item {} to id {}", item, item_id))
+    let session = sessions.for_request(&req).await;
+    let mut session = session.lock().await;
+    session.edit(format!("Set item {} to {}", item, item_id), |player| {
+        player.items[item] = item_id;
+        HttpResponse::Ok().body(format!("Successfully set item {} to id {}", item, item_id))
+    })
 }
 
-/// Either increments or decrements the skill points of a certain ability. This is unrestricted by
-/// the level of the player and the amount of skill points used. This can be left to the frontend.
-pub async fn change_skill_point(player_data: web::Data<Mutex<Player>>, path: web::Path<(usize, String)>) -> impl actix_web::Responder {
+/// Either increments or decrements the skill points of a certain ability, where abilities 0 to 3
+/// are Q, W, E and R. Points are checked against the player's level and champion.
+pub async fn change_skill_point(req: HttpRequest, sessions: web::Data<Sessions>, path: web::Path<(usize, String)>) -> impl actix_web::Responder {
     let (ability, which_way) = path.into_inner();
-    if ability > 3 || ability == 0 {
-        return HttpResponse::InternalServerError().body("Ability index out of bounds");
+    if ability > skills::ULTIMATE {
+        return HttpResponse::BadRequest().body("Ability index out of bounds");
     }
 
-    let mut player = player_data.lock().await;
-
-    match which_way.as_str() {
-        "inc" => {
-            player.skill_points[ability] += 1;
-            HttpResponse::Ok().body(format!("Successfully increased the skill point of ability {}", ability))
-        }
-        "dec" => {
-            player.skill_points[ability] -= 1;
-            HttpResponse::Ok().body(format!("Successfully decreased the skill point of ability {}", ability))
-        }
-        _ => {
-            HttpResponse::BadRequest().body("Invalid input: needs to be either 'inc' or 'dec'")
+    let session = sessions.for_request(&req).await;
+    let mut session = session.lock().await;
+    let name = skills::ABILITY_NAMES[ability];
+    session.edit(format!("{} {}", if which_way == "dec" { "Rank down" } else { "Rank up" }, name), |player| {
+        match which_way.as_str() {
+            "inc" => {
+                let mut skill_points = player.skill_points;
+                skill_points[ability] += 1;
+                if let Err(err) = skills::validate(&player.champ, &skill_points, player.level) {
+                    return HttpResponse::BadRequest().body(format!("Cannot rank up {}: {}", name, err));
+                }
+                player.skill_points = skill_points;
+                HttpResponse::Ok().body(format!("Successfully increased the skill point of ability {}", name))
+            }
+            "dec" => {
+                if player.skill_points[ability] == 0 {
+                    return HttpResponse::BadRequest().body(format!("Cannot rank down {}: it has no skill points", name));
+                }
+                player.skill_points[ability] -= 1;
+                HttpResponse::Ok().body(format!("Successfully decreased the skill point of ability {}", name))
+            }
+            _ => {
+                HttpResponse::BadRequest().body("Invalid input: needs to be either 'inc' or 'dec'")
+            }
         }
+    })
+}
+
+/// Moves the player to a given level, as long as the skill points already spent are still valid.
+fn change_level(player: &mut Player, level: u8) -> HttpResponse {
+    if !(MIN_LEVEL..=MAX_LEVEL).contains(&level) {
+        return HttpResponse::BadRequest().body(format!("Level must be between {} and {}", MIN_LEVEL, MAX_LEVEL));
     }
+    if let Err(err) = skills::validate(&player.champ, &player.skill_points, level) {
+        return HttpResponse::BadRequest().body(format!("Cannot change level: {}", err));
+    }
+
+    player.level = level;
+    HttpResponse::Ok().body(format!("Successfully set level to {}", level))
+}
+
+/// Sets the player's level.
+pub async fn set_level(req: HttpRequest, sessions: web::Data<Sessions>, path: web::Path<u8>) -> impl actix_web::Responder {
+    let session = sessions.for_request(&req).await;
+    let mut session = session.lock().await;
+    let level = path.into_inner();
+    session.edit(format!("Set level to {}", level), |player| change_level(player, level))
+}
+
+/// Increases the player's level by one.
+pub async fn level_up(req: HttpRequest, sessions: web::Data<Sessions>) -> impl actix_web::Responder {
+    let session = sessions.for_request(&req).await;
+    let mut session = session.lock().await;
+    let level = session.player.level.saturating_add(1);
+    session.edit("Level up", |player| change_level(player, level))
+}
+
+/// Decreases the player's level by one.
+pub async fn level_down(req: HttpRequest, sessions: web::Data<Sessions>) -> impl actix_web::Responder {
+    let session = sessions.for_request(&req).await;
+    let mut session = session.lock().await;
+    let level = session.player.level.saturating_sub(1);
+    session.edit("Level down", |player| change_level(player, level))
 }
 
 /// This structure is only useful for displaying the output of display_stats.
@@ -165,54 +251,231 @@ struct UserStats {
     armor: f64,
     armor_penetration_and_lethality: (f64, f64),
     attack_damage: f64,
-    attack_speed: f64,
+    attack_speed: AttackSpeed,
     critical_strike_chance: f64,
+    critical_strike_damage: f64,
     heal_and_shield_power: f64,
     health_and_regen: (f64, f64),
     lifesteal_and_omnivamp: (f64, f64),
+    physical_vamp: f64,
     magic_penetration: (f64, f64),
     magic_resistance: f64,
     mana_and_regen: (f64, f64),
     movespeed: f64,
     ability_haste: f64,
     tenacity: f64,
+    slow_resistance: f64,
+    attack_range: f64,
+    adaptive_force: f64,
+    effective_health: EffectiveHealth,
+}
+
+impl UserStats {
+    /// Picks out the stats the user cares about from the resolved stats.
+    fn from_resolved(resolved: &ResolvedStats, attack_speed: AttackSpeed) -> Self {
+        UserStats {
+            ability_power: resolved[StatKind::AbilityPower],
+            armor: resolved[StatKind::Armor],
+            armor_penetration_and_lethality: (resolved[StatKind::ArmorPenetration], resolved[StatKind::Lethality]),
+            attack_damage: resolved[StatKind::AttackDamage],
+            attack_speed,
+            critical_strike_chance: resolved[StatKind::CriticalStrikeChance],
+            critical_strike_damage: critical_strike_damage(resolved),
+            heal_and_shield_power: resolved[StatKind::HealAndShieldPower],
+            health_and_regen: (resolved[StatKind::Health], resolved[StatKind::HealthRegen]),
+            lifesteal_and_omnivamp: (resolved[StatKind::Lifesteal], resolved[StatKind::Omnivamp]),
+            physical_vamp: resolved[StatKind::PhysicalVamp],
+            magic_penetration: (resolved[StatKind::MagicPenetrationPercent], resolved[StatKind::MagicPenetration]),
+            magic_resistance: resolved[StatKind::MagicResistance],
+            mana_and_regen: (resolved[StatKind::Mana], resolved[StatKind::ManaRegen]),
+            movespeed: resolved[StatKind::Movespeed],
+            ability_haste: resolved[StatKind::AbilityHaste],
+            tenacity: resolved[StatKind::Tenacity],
+            slow_resistance: resolved[StatKind::SlowResistance],
+            attack_range: resolved[StatKind::AttackRange],
+            adaptive_force: resolved[StatKind::AdaptiveForce],
+            effective_health: damage::effective_health(resolved, &ResolvedStats::new(), 0.5),
+        }
+    }
+}
+
+/// Applies the champion's critical strike damage modifier, which defaults to 1 when missing.
+pub fn critical_strike_damage(resolved: &ResolvedStats) -> f64 {
+    let modifier = resolved[StatKind::CriticalStrikeDamageModifier];
+    let modifier = if modifier == 0.0 { 1.0 } else { modifier };
+    resolved[StatKind::CriticalStrikeDamage] * modifier
 }
 
 /// Shows the stats that the user cares about post-calculations
-pub async fn display_stats(player_data: web::Data<Mutex<Player>>) -> impl actix_web::Responder {
-    let player = player_data.lock().await;
+pub async fn display_stats(req: HttpRequest, sessions: web::Data<Sessions>, game_data: web::Data<SharedGameData>) -> impl actix_web::Responder {
+    let session = sessions.for_request(&req).await;
+    let session = session.lock().await;
+    let player = &session.player;
+
+    let sources = player.sources(&game_data.get());
+    let resolved = breakdown::resolve(&sources, player.level, &player.champ);
+    let attack_speed = stats::attack_speed(&player.base_stats, &breakdown::bonus_stats(&sources), player.level, &player.champ);
+    let new_stats = UserStats::from_resolved(&resolved, attack_speed);
+    
+    HttpResponse::Ok().json(new_stats)
+}
 
-    let level = player.level as f64 - 1.0;
+/// This structure is only useful for displaying the output of stats_breakdown.
+#[derive(Serialize)]
+struct PlayerBreakdown {
+    champion: String,
+    level: u8,
+    stats: Vec<StatBreakdown>,
+}
 
-    let mut merged = Stats::add_stats(&player.base_stats, &player.stats);
+/// Shows every stat along with each contribution to it, in the order they are applied. Stats
+/// without any contributions are left out.
+pub async fn stats_breakdown(req: HttpRequest, sessions: web::Data<Sessions>, game_data: web::Data<SharedGameData>) -> impl actix_web::Responder {
+    let session = sessions.for_request(&req).await;
+    let session = session.lock().await;
+    let player = &session.player;
 
-    for item in player.items {
-        if item != 0 {
-            if let Some(item_stats) = &get_item_stats(item).await {
-                merged = Stats::add_stats(&merged, item_stats);
-            } else { 
-                println!("No item in slot {}", item);
-            }
+    let sources = player.sources(&game_data.get());
+    let stats = breakdown::explain(&sources, player.level, &player.champ)
+        .into_iter()
+        .filter(|stat| !stat.contributions.is_empty())
+        .collect();
+
+    HttpResponse::Ok().json(PlayerBreakdown {
+        champion: player.champ.clone(),
+        level: player.level,
+        stats,
+    })
+}
+
+/// This structure is only useful for displaying the output of the growth endpoints.
+#[derive(Serialize)]
+struct LevelStats {
+    level: u8,
+    #[serde(flatten)]
+    stats: UserStats,
+}
+
+/// stores every resolved stat that is not always zero, by level, for drawing charts
+#[derive(Serialize, Debug, PartialEq)]
+struct GrowthSeries {
+    levels: Vec<u8>,
+    stats: BTreeMap<&'static str, Vec<f64>>,
+}
+
+/// This structure is only useful for displaying the output of the growth endpoints.
+#[derive(Serialize)]
+struct GrowthTable {
+    champion: String,
+    levels: Vec<LevelStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    series: Option<GrowthSeries>,
+}
+
+/// Resolves the stats of a champion at every level with the same stat sources, optionally along
+/// with chart-ready series.
+fn growth_table(sources: &[Source], champion: &str, series: bool) -> GrowthTable {
+    let base = breakdown::base_stats(sources);
+    let bonus = breakdown::bonus_stats(sources);
+    let mut levels = Vec::new();
+    let mut values: BTreeMap<&'static str, Vec<f64>> = BTreeMap::new();
+
+    for level in MIN_LEVEL..=MAX_LEVEL {
+        let resolved = breakdown::resolve(sources, level, champion);
+        let attack_speed = stats::attack_speed(&base, &bonus, level, champion);
+        for (kind, value) in resolved.iter() {
+            values.entry(kind.key()).or_default().push(value);
         }
+        levels.push(LevelStats { level, stats: UserStats::from_resolved(&resolved, attack_speed) });
     }
-    
-    let new_stats = UserStats {
-        ability_power: merged.ability_power.flat + merged.ability_power.per_level * level * (0.7025 + 0.0175 * level),
-        armor: merged.armor.flat + merged.armor.per_level * level * (0.7025 + 0.0175 * level),
-        armor_penetration_and_lethality: (merged.armor_penetration.percent, merged.lethality.flat),
-        attack_damage: merged.attack_damage.flat + merged.attack_damage.per_level * level * (0.7025 + 0.0175 * level),
-        attack_speed: merged.attack_speed.flat + ((merged.attack_speed.percent / 100.0 + merged.attack_speed.per_level * level * (0.7025 + 0.0175 * level)) * merged.attack_speed.flat),
-        critical_strike_chance: merged.critical_strike_chance.flat + merged.critical_strike_chance.per_level * level * (0.7025 + 0.0175 * level),
-        heal_and_shield_power: merged.heal_and_shield_power.flat + merged.heal_and_shield_power.per_level * level * (0.7025 * 0.0175 * level),
-        health_and_regen: (merged.health.flat + merged.health.per_level * level * (0.7025 * 0.0175 * level), merged.health_regen.flat + merged.health_regen.per_level * level * (0.7025 * 0.0175 * level)),
-        lifesteal_and_omnivamp: (merged.lifesteal.percent + merged.lifesteal.per_level * level * (0.7025 * 0.0175 * level), merged.omnivamp.percent + merged.omnivamp.per_level * level * (0.7025 * 0.0175 * level)),
-        magic_penetration: (merged.magic_penetration.percent, merged.magic_penetration.flat),
-        magic_resistance: merged.magic_resistance.flat + merged.magic_resistance.per_level * level * (0.7025 * 0.0175 * level),
-        mana_and_regen: (merged.mana.flat + merged.mana.per_level * level * (0.7025 * 0.0175 * level), merged.mana_regen.flat + merged.mana_regen.per_level * level * (0.7025 * 0.0175 * level)),
-        movespeed: merged.movespeed.flat + merged.movespeed.per_level * level * (0.7025 * 0.0175 * level),
-        ability_haste: merged.ability_haste.flat + merged.ability_haste.per_level * level * (0.7025 * 0.0175 * level),
-        tenacity: merged.tenacity.percent + merged.tenacity.per_level * level * (0.7025 * 0.0175 * level)
+
+    values.retain(|_, values| values.iter().any(|&value| value != 0.0));
+    GrowthTable {
+        champion: champion.to_string(),
+        levels,
+        series: series.then(|| GrowthSeries { levels: (MIN_LEVEL..=MAX_LEVEL).collect(), stats: values }),
+    }
+}
+
+/// This structure is only useful for reading the query of the growth endpoints.
+#[derive(Deserialize)]
+pub struct GrowthQuery {
+    /// whether to include chart-ready series
+    #[serde(default)]
+    series: bool,
+    /// comma separated item ids, only used when looking at a champion other than the player's
+    #[serde(default)]
+    items: Option<String>,
+}
+
+/// Shows the player's stats at every level from 1 to 18 with their current items.
+pub async fn player_growth(req: HttpRequest, sessions: web::Data<Sessions>, game_data: web::Data<SharedGameData>, query: web::Query<GrowthQuery>) -> impl actix_web::Responder {
+    let session = sessions.for_request(&req).await;
+    let session = session.lock().await;
+    let player = &session.player;
+
+    HttpResponse::Ok().json(growth_table(&player.sources(&game_data.get()), &player.champ, query.series))
+}
+
+/// Shows a champion's stats at every level from 1 to 18 with the items given in the query.
+pub async fn champion_growth(game_data: web::Data<SharedGameData>, champion: web::Path<String>, query: web::Query<GrowthQuery>) -> impl actix_web::Responder {
+    let champion = champion.into_inner();
+    let data = game_data.get();
+    let Some(base_stats) = data.champion(&champion) else {
+        return HttpResponse::NotFound().body(format!("Unknown champion {}", champion));
     };
-    
-    HttpResponse::Ok().json(new_stats)
+
+    let items = match query.items.as_deref().unwrap_or("").split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| item.parse::<u16>().map_err(|_| format!("Invalid item id {}", item)))
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(items) => items,
+        Err(err) => return HttpResponse::BadRequest().body(err),
+    };
+
+    let mut sources = vec![Source {
+        kind: SourceKind::Champion,
+        id: champion.clone(),
+        name: champion.clone(),
+        stats: base_stats.clone(),
+    }];
+    match data.item_sources(&items) {
+        Ok(item_sources) => sources.extend(item_sources),
+        Err(err) => return HttpResponse::BadRequest().body(err),
+    }
+
+    HttpResponse::Ok().json(growth_table(&sources, &champion, query.series))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluate::Build;
+    use crate::gamedata::tests::sample;
+
+    fn sources(champion: &str, items: &[u16]) -> Vec<Source> {
+        let build = Build { champion: champion.to_string(), level: 1, items: items.to_vec(), skill_points: [0; 4] };
+        build.sources(&sample()).unwrap().0
+    }
+
+    #[test]
+    fn growth_covers_every_level_with_items_held() {
+        let table = growth_table(&sources("Garen", &[1036]), "Garen", false);
+        assert_eq!(table.levels.len(), 18);
+        assert_eq!(table.levels[0].level, 1);
+        assert_eq!(table.levels[0].stats.attack_damage, 79.0);
+        assert_eq!(table.levels[17].stats.attack_damage, 79.0 + 4.5 * stats::growth(18));
+        assert!(table.series.is_none());
+    }
+
+    #[test]
+    fn series_skip_stats_that_are_always_zero() {
+        let series = growth_table(&sources("Garen", &[]), "Garen", true).series.unwrap();
+        assert_eq!(series.levels, (1..=18).collect::<Vec<u8>>());
+        assert_eq!(series.stats["health"].len(), 18);
+        assert_eq!(series.stats["health"][0], 690.0);
+        assert!(!series.stats.contains_key("abilityPower"));
+    }
 }
@@ -0,0 +1,124 @@
+/*
+ * File: registry.rs
+ *
+ * Copyright (c) 2025 Alan Fung
+ *
+ * Description: describes how every stat should be displayed, so the client does not need to
+ * hardcode stat names
+ */
+use actix_web::HttpResponse;
+use serde::Serialize;
+
+use crate::stats::{StatKind, StatUnit};
+
+/// where a stat belongs in the stat panel
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum StatGroup {
+    Offense,
+    Defense,
+    Utility,
+    /// used in calculations but not usually shown to the user
+    Internal,
+}
+
+/// stores how a stat should be displayed
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StatInfo {
+    pub id: StatKind,
+    pub name: &'static str,
+    /// name of the icon under the client's assets/stats directory
+    pub icon: Option<&'static str>,
+    pub unit: StatUnit,
+    /// number of decimal places to show
+    pub precision: u8,
+    pub group: StatGroup,
+    /// stat that is shown next to this one, such as health regen next to health
+    pub paired_with: Option<StatKind>,
+}
+
+/// Describes how a given stat should be displayed.
+pub fn stat_info(kind: StatKind) -> StatInfo {
+    use StatGroup::*;
+    use StatKind::*;
+
+    let (name, icon, precision, group, paired_with) = match kind {
+        AbilityPower => ("Ability Power", Some("ap"), 0, Offense, None),
+        Armor => ("Armor", Some("armor"), 0, Defense, None),
+        ArmorPenetration => ("Armor Penetration", Some("apen"), 0, Offense, Some(Lethality)),
+        AttackDamage => ("Attack Damage", Some("ad"), 0, Offense, None),
+        AttackSpeed => ("Attack Speed", Some("as"), 3, Offense, None),
+        AttackSpeedRatio => ("Attack Speed Ratio", None, 3, Internal, None),
+        CooldownReduction => ("Cooldown Reduction", None, 0, Utility, None),
+        CriticalStrikeChance => ("Critical Strike Chance", Some("critchance"), 0, Offense, None),
+        CriticalStrikeDamage => ("Critical Strike Damage", Some("critdmg"), 0, Offense, None),
+        CriticalStrikeDamageModifier => ("Critical Strike Damage Modifier", None, 2, Internal, None),
+        GoldPer10 => ("Gold per 10 Seconds", None, 1, Utility, None),
+        HealAndShieldPower => ("Heal and Shield Power", Some("hsp"), 0, Utility, None),
+        Health => ("Health", Some("hp"), 0, Defense, Some(HealthRegen)),
+        HealthRegen => ("Health Regen", Some("hpregen"), 1, Defense, Some(Health)),
+        Lethality => ("Lethality", Some("apen"), 0, Offense, Some(ArmorPenetration)),
+        Lifesteal => ("Life Steal", Some("lifesteal"), 0, Offense, Some(Omnivamp)),
+        PhysicalVamp => ("Physical Vamp", None, 0, Offense, None),
+        MagicPenetration => ("Magic Penetration", Some("mpen"), 0, Offense, Some(MagicPenetrationPercent)),
+        MagicPenetrationPercent => ("Percent Magic Penetration", Some("mpen"), 0, Offense, Some(MagicPenetration)),
+        MagicResistance => ("Magic Resistance", Some("mres"), 0, Defense, None),
+        Mana => ("Mana", Some("mana"), 0, Utility, Some(ManaRegen)),
+        ManaRegen => ("Mana Regen", Some("manaregen"), 1, Utility, Some(Mana)),
+        Movespeed => ("Move Speed", Some("ms"), 0, Utility, None),
+        AbilityHaste => ("Ability Haste", Some("ah"), 0, Utility, None),
+        Omnivamp => ("Omnivamp", Some("omnivamp"), 0, Offense, Some(Lifesteal)),
+        Tenacity => ("Tenacity", Some("tenacity"), 0, Defense, None),
+        SlowResistance => ("Slow Resistance", None, 0, Defense, None),
+        AttackRange => ("Attack Range", Some("ar"), 0, Offense, None),
+        AcquisitionRadius => ("Acquisition Radius", None, 0, Internal, None),
+        AdaptiveForce => ("Adaptive Force", None, 0, Offense, None),
+        Shield => ("Shield", None, 0, Defense, None),
+    };
+
+    StatInfo {
+        id: kind,
+        name,
+        icon,
+        unit: kind.unit(),
+        precision,
+        group,
+        paired_with,
+    }
+}
+
+/// Describes every stat, in the same order as serialized stats.
+pub fn registry() -> Vec<StatInfo> {
+    StatKind::ALL.into_iter().map(stat_info).collect()
+}
+
+/// Retrieves the display information of every stat.
+pub async fn get_stat_registry() -> impl actix_web::Responder {
+    HttpResponse::Ok().json(registry())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn icons_exist_in_client_assets() {
+        let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("../client/src/assets/stats");
+        for info in registry() {
+            if let Some(icon) = info.icon {
+                assert!(assets.join(format!("{}.png", icon)).exists(), "missing icon for {}", info.id.key());
+            }
+        }
+    }
+
+    #[test]
+    fn pairs_point_back() {
+        for info in registry() {
+            if let Some(pair) = info.paired_with {
+                assert_eq!(stat_info(pair).paired_with, Some(info.id), "{} is not paired back", pair.key());
+            }
+        }
+    }
+}
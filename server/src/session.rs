@@ -0,0 +1,396 @@
+/*
+ * File: session.rs
+ *
+ * Copyright (c) 2025 Alan Fung
+ *
+ * Description: keeps a separate player for every user of the server
+ */
+use actix_web::body::MessageBody;
+use actix_web::cookie::Cookie;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{web, Error, HttpMessage, HttpRequest, HttpResponse};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::player::Player;
+
+/// name of the cookie holding the session id
+pub const SESSION_COOKIE: &str = "session";
+/// sessions that go unused for this long are removed
+const SESSION_TTL: Duration = Duration::from_secs(2 * 60 * 60);
+/// most actions that can be undone
+const HISTORY_LIMIT: usize = 50;
+
+/// stores everything that belongs to a single user
+#[derive(Debug, Default)]
+pub struct Session {
+    pub player: Player,
+    pub history: History,
+}
+
+impl Session {
+    /// Runs an action on the player, recording it in the history if it changed the player.
+    pub fn edit<R>(&mut self, action: impl Into<String>, f: impl FnOnce(&mut Player) -> R) -> R {
+        let before = self.player.clone();
+        let result = f(&mut self.player);
+        if self.player != before {
+            self.history.record(action.into(), before);
+        }
+        result
+    }
+
+    /// Restores the player from before the last action. Returns the action that was undone.
+    pub fn undo(&mut self) -> Option<String> {
+        let step = self.history.undo.pop_back()?;
+        let after = std::mem::replace(&mut self.player, step.player);
+        self.history.redo.push(Step { action: step.action.clone(), player: after });
+        Some(step.action)
+    }
+
+    /// Applies the last undone action again. Returns the action that was redone.
+    pub fn redo(&mut self) -> Option<String> {
+        let step = self.history.redo.pop()?;
+        let before = std::mem::replace(&mut self.player, step.player);
+        self.history.undo.push_back(Step { action: step.action.clone(), player: before });
+        Some(step.action)
+    }
+}
+
+/// stores a snapshot of the player on the other side of an action
+#[derive(Debug, Clone)]
+struct Step {
+    action: String,
+    player: Player,
+}
+
+/// stores the actions that can be undone and redone, oldest first
+#[derive(Debug, Default)]
+pub struct History {
+    undo: VecDeque<Step>,
+    redo: Vec<Step>,
+}
+
+/// stores an action in the history and whether it is currently undone
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub action: String,
+    pub undone: bool,
+}
+
+impl History {
+    /// Records an action along with the player from before it. Recording a new action discards
+    /// anything that could have been redone.
+    fn record(&mut self, action: String, before: Player) {
+        self.redo.clear();
+        self.undo.push_back(Step { action, player: before });
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Lists every action in the order they were made.
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        let done = self.undo.iter().map(|step| HistoryEntry { action: step.action.clone(), undone: false });
+        let undone = self.redo.iter().rev().map(|step| HistoryEntry { action: step.action.clone(), undone: true });
+        done.chain(undone).collect()
+    }
+}
+
+struct Entry {
+    session: Arc<Mutex<Session>>,
+    last_seen: Instant,
+}
+
+/// stores the id of a session started while handling a request, so its cookie can be set
+#[derive(Clone)]
+struct IssuedSession(String);
+
+/// stores every active session by id
+pub struct Sessions {
+    entries: Mutex<HashMap<String, Entry>>,
+    ttl: Duration,
+}
+
+impl Default for Sessions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sessions {
+    pub fn new() -> Self {
+        Self::with_ttl(SESSION_TTL)
+    }
+
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Sessions {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    /// Creates a new session and returns its id. Expired sessions are removed first, so the
+    /// sessions only grow while they are in use.
+    pub async fn create(&self) -> String {
+        self.start().await.0
+    }
+
+    /// Creates a new session and returns its id along with the session itself.
+    async fn start(&self) -> (String, Arc<Mutex<Session>>) {
+        let id = Uuid::new_v4().simple().to_string();
+        let session = Arc::new(Mutex::new(Session::default()));
+        let mut entries = self.entries.lock().await;
+        let now = Instant::now();
+        entries.retain(|_, entry| now.duration_since(entry.last_seen) < self.ttl);
+
+        entries.insert(id.clone(), Entry {
+            session: session.clone(),
+            last_seen: now,
+        });
+        (id, session)
+    }
+
+    /// Retrieves the session with a given id. Returns None if the server never issued the id or
+    /// the session expired after going unused.
+    pub async fn get(&self, id: &str) -> Option<Arc<Mutex<Session>>> {
+        let mut entries = self.entries.lock().await;
+        let now = Instant::now();
+        let expired = now.duration_since(entries.get(id)?.last_seen) >= self.ttl;
+        if expired {
+            entries.remove(id);
+            return None;
+        }
+
+        let entry = entries.get_mut(id)?;
+        entry.last_seen = now;
+        Some(entry.session.clone())
+    }
+
+    /// Retrieves the session named by a request's session cookie. Requests without a cookie, or
+    /// with the id of a session that does not exist anymore, start a new session, and
+    /// issue_session_cookie sets its cookie on the response.
+    pub async fn for_request(&self, req: &HttpRequest) -> Arc<Mutex<Session>> {
+        let issued = req.extensions().get::<IssuedSession>().cloned();
+        if let Some(IssuedSession(id)) = issued {
+            if let Some(session) = self.get(&id).await {
+                return session;
+            }
+        }
+
+        if let Some(id) = session_id(req) {
+            if let Some(session) = self.get(&id).await {
+                return session;
+            }
+        }
+
+        let (id, session) = self.start().await;
+        req.extensions_mut().insert(IssuedSession(id));
+        session
+    }
+
+    /// Removes a session. Returns false if there was no such session.
+    pub async fn remove(&self, id: &str) -> bool {
+        self.entries.lock().await.remove(id).is_some()
+    }
+}
+
+/// Reads the session id from a request's session cookie.
+pub fn session_id(req: &HttpRequest) -> Option<String> {
+    req.cookie(SESSION_COOKIE).map(|cookie| cookie.value().to_string())
+}
+
+/// Builds the session cookie for a given session id.
+fn session_cookie(id: String) -> Cookie<'static> {
+    Cookie::build(SESSION_COOKIE, id).path("/").http_only(true).finish()
+}
+
+/// Sets the session cookie on responses to requests that started a new session.
+pub async fn issue_session_cookie(req: ServiceRequest, next: Next<impl MessageBody>) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let mut res = next.call(req).await?;
+    let issued = res.request().extensions().get::<IssuedSession>().cloned();
+    if let Some(IssuedSession(id)) = issued {
+        res.response_mut().add_cookie(&session_cookie(id))?;
+    }
+    Ok(res)
+}
+
+/// This structure is only useful for displaying the output of create_session.
+#[derive(Serialize)]
+struct CreatedSession {
+    id: String,
+}
+
+/// Starts a new session with its own player and sets the session cookie.
+pub async fn create_session(sessions: web::Data<Sessions>) -> impl actix_web::Responder {
+    let id = sessions.create().await;
+
+    HttpResponse::Ok()
+        .cookie(session_cookie(id.clone()))
+        .json(CreatedSession { id })
+}
+
+/// Ends the session named by the session cookie.
+pub async fn delete_session(req: HttpRequest, sessions: web::Data<Sessions>) -> impl actix_web::Responder {
+    let id = match session_id(&req) {
+        Some(id) => id,
+        None => return HttpResponse::NotFound().body("Session not found"),
+    };
+
+    if sessions.remove(&id).await {
+        let mut cookie = Cookie::build(SESSION_COOKIE, "").path("/").finish();
+        cookie.make_removal();
+        HttpResponse::Ok().cookie(cookie).body(format!("Successfully deleted session {}", id))
+    } else {
+        HttpResponse::NotFound().body("Session not found")
+    }
+}
+
+/// Undoes the session's last player action.
+pub async fn undo(req: HttpRequest, sessions: web::Data<Sessions>) -> impl actix_web::Responder {
+    let session = sessions.for_request(&req).await;
+    let mut session = session.lock().await;
+    match session.undo() {
+        Some(action) => HttpResponse::Ok().body(format!("Undid {}", action)),
+        None => HttpResponse::BadRequest().body("Nothing to undo"),
+    }
+}
+
+/// Redoes the session's last undone player action.
+pub async fn redo(req: HttpRequest, sessions: web::Data<Sessions>) -> impl actix_web::Responder {
+    let session = sessions.for_request(&req).await;
+    let mut session = session.lock().await;
+    match session.redo() {
+        Some(action) => HttpResponse::Ok().body(format!("Redid {}", action)),
+        None => HttpResponse::BadRequest().body("Nothing to redo"),
+    }
+}
+
+/// Lists the session's player actions, including those that were undone.
+pub async fn get_history(req: HttpRequest, sessions: web::Data<Sessions>) -> impl actix_web::Responder {
+    let session = sessions.for_request(&req).await;
+    let session = session.lock().await;
+    HttpResponse::Ok().json(session.history.entries())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::middleware::from_fn;
+    use actix_web::test::{self, TestRequest};
+    use actix_web::App;
+
+    #[actix_web::test]
+    async fn sessions_are_isolated() {
+        let sessions = Sessions::new();
+        let first = sessions.create().await;
+        let second = sessions.create().await;
+        assert_ne!(first, second);
+
+        sessions.get(&first).await.unwrap().lock().await.player.level = 11;
+        assert_eq!(sessions.get(&first).await.unwrap().lock().await.player.level, 11);
+        assert_eq!(sessions.get(&second).await.unwrap().lock().await.player.level, 1);
+    }
+
+    #[actix_web::test]
+    async fn requests_are_routed_by_cookie() {
+        let sessions = Sessions::new();
+        let id = sessions.create().await;
+        sessions.get(&id).await.unwrap().lock().await.player.champ = "Garen".to_string();
+
+        let req = TestRequest::default().cookie(Cookie::new(SESSION_COOKIE, id)).to_http_request();
+        assert_eq!(sessions.for_request(&req).await.lock().await.player.champ, "Garen");
+
+        let req = TestRequest::default().to_http_request();
+        sessions.for_request(&req).await.lock().await.player.level = 6;
+        assert!(req.extensions().get::<IssuedSession>().is_some());
+        assert_eq!(sessions.for_request(&req).await.lock().await.player.level, 6);
+        assert_eq!(sessions.entries.lock().await.len(), 2);
+
+        let other = TestRequest::default().to_http_request();
+        assert_eq!(sessions.for_request(&other).await.lock().await.player.level, 1);
+    }
+
+    #[actix_web::test]
+    async fn only_issued_ids_have_sessions() {
+        let sessions = Sessions::new();
+        assert!(sessions.get("made-up").await.is_none());
+        assert!(sessions.entries.lock().await.is_empty());
+
+        let req = TestRequest::default().cookie(Cookie::new(SESSION_COOKIE, "made-up")).to_http_request();
+        sessions.for_request(&req).await;
+        let issued = req.extensions().get::<IssuedSession>().cloned().unwrap();
+        assert_ne!(issued.0, "made-up");
+        assert!(sessions.get("made-up").await.is_none());
+        assert!(sessions.get(&issued.0).await.is_some());
+    }
+
+    #[actix_web::test]
+    async fn new_sessions_set_the_cookie() {
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(Sessions::new()))
+                .wrap(from_fn(issue_session_cookie))
+                .route("/history", web::get().to(get_history)),
+        ).await;
+
+        let res = test::call_service(&app, TestRequest::get().uri("/history").to_request()).await;
+        let cookie = res.response().cookies().find(|cookie| cookie.name() == SESSION_COOKIE).unwrap();
+        let id = cookie.value().to_string();
+
+        let req = TestRequest::get().uri("/history").cookie(Cookie::new(SESSION_COOKIE, id)).to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.response().cookies().count(), 0);
+    }
+
+    #[actix_web::test]
+    async fn unused_sessions_expire() {
+        let sessions = Sessions::with_ttl(Duration::ZERO);
+        let id = sessions.create().await;
+
+        assert!(sessions.get(&id).await.is_none());
+        assert!(sessions.entries.lock().await.is_empty());
+
+        sessions.create().await;
+        sessions.create().await;
+        assert_eq!(sessions.entries.lock().await.len(), 1);
+    }
+
+    #[test]
+    fn undo_and_redo_restore_players() {
+        let mut session = Session::default();
+        session.edit("set level 6", |player| player.level = 6);
+        session.edit("add item 3071", |player| player.items[0] = 3071);
+        session.edit("nothing", |_| {});
+        assert_eq!(session.history.entries().len(), 2);
+
+        assert_eq!(session.undo().as_deref(), Some("add item 3071"));
+        assert_eq!(session.player.items[0], 0);
+        assert_eq!(session.player.level, 6);
+        assert_eq!(session.history.entries()[1], HistoryEntry { action: "add item 3071".to_string(), undone: true });
+
+        assert_eq!(session.redo().as_deref(), Some("add item 3071"));
+        assert_eq!(session.player.items[0], 3071);
+        assert_eq!(session.redo(), None);
+
+        session.undo();
+        session.edit("set level 11", |player| player.level = 11);
+        assert_eq!(session.redo(), None);
+        assert_eq!(session.history.entries().iter().map(|entry| entry.action.as_str()).collect::<Vec<_>>(), ["set level 6", "set level 11"]);
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut session = Session::default();
+        for i in 0..HISTORY_LIMIT + 10 {
+            session.edit(format!("step {}", i), |player| player.items[0] = i as u16 + 1);
+        }
+        assert_eq!(session.history.entries().len(), HISTORY_LIMIT);
+        while session.undo().is_some() {}
+        assert_eq!(session.player.items[0], 10);
+    }
+}
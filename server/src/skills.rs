@@ -0,0 +1,165 @@
+/*
+ * File: skills.rs
+ *
+ * Copyright (c) 2025 Alan Fung
+ *
+ * Description: rules for how skill points can be spent at each level
+ */
+
+/// index of the ultimate in a player's skill points
+pub const ULTIMATE: usize = 3;
+/// names of the abilities in the order of a player's skill points
+pub const ABILITY_NAMES: [&str; 4] = ["Q", "W", "E", "R"];
+
+/// stores how a champion's abilities can be ranked up
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkillRules {
+    /// highest rank each ability can take with skill points, where 0 means the ability cannot
+    /// take skill points at all
+    pub max_ranks: [u8; 4],
+    /// levels at which the ultimate can be ranked up, or empty if the ultimate ranks up like a
+    /// basic ability
+    pub ultimate_levels: &'static [u8],
+}
+
+const DEFAULT_RULES: SkillRules = SkillRules {
+    max_ranks: [5, 5, 5, 3],
+    ultimate_levels: &[6, 11, 16],
+};
+
+/// Returns the skill rules of a given champion. Most champions use the default rules.
+pub fn skill_rules(champion: &str) -> SkillRules {
+    match champion {
+        // transforming champions whose ultimate is ranked automatically
+        "Jayce" => SkillRules {
+            max_ranks: [6, 6, 6, 0],
+            ultimate_levels: &[],
+        },
+        // transforming champions with an ultimate from level 1
+        "Elise" | "Nidalee" | "Karma" => SkillRules {
+            max_ranks: [5, 5, 5, 4],
+            ultimate_levels: &[1, 6, 11, 16],
+        },
+        // four basic abilities and no ultimate
+        "Udyr" => SkillRules {
+            max_ranks: [6, 6, 6, 6],
+            ultimate_levels: &[],
+        },
+        _ => DEFAULT_RULES,
+    }
+}
+
+impl SkillRules {
+    /// Returns the highest rank an ability can have at a given level. A basic ability can only
+    /// be ranked to rank n at level 2n - 1.
+    pub fn max_rank(&self, ability: usize, level: u8) -> u8 {
+        let max = self.max_ranks[ability];
+        if ability == ULTIMATE && !self.ultimate_levels.is_empty() {
+            (self.ultimate_levels.iter().filter(|&&unlock| level >= unlock).count() as u8).min(max)
+        } else {
+            level.div_ceil(2).min(max)
+        }
+    }
+
+    /// Checks that skill points could have been spent this way by a given level.
+    pub fn validate(&self, skill_points: &[u8; 4], level: u8) -> Result<(), String> {
+        let total: u32 = skill_points.iter().map(|&points| points as u32).sum();
+        if total > level as u32 {
+            return Err(format!("{} skill points are spent, but level {} only has {}", total, level, level));
+        }
+
+        for (ability, &points) in skill_points.iter().enumerate() {
+            let max = self.max_rank(ability, level);
+            if points > max {
+                return Err(if self.max_ranks[ability] == 0 {
+                    format!("{} cannot be ranked up with skill points", ABILITY_NAMES[ability])
+                } else if max == self.max_ranks[ability] {
+                    format!("{} is already at its maximum rank of {}", ABILITY_NAMES[ability], max)
+                } else {
+                    format!("{} can only be rank {} at level {}", ABILITY_NAMES[ability], max, level)
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Spends a champion's skill points the usual way up to a given level: the ultimate whenever it
+/// can be ranked up, one point in each basic ability, then maxing Q, W and E in that order.
+pub fn default_skill_points(champion: &str, level: u8) -> [u8; 4] {
+    let rules = skill_rules(champion);
+    let mut points = [0; 4];
+
+    for level in 1..=level {
+        let can_rank = |ability: usize, points: &[u8; 4]| points[ability] < rules.max_rank(ability, level);
+        let ability = (!rules.ultimate_levels.is_empty() && can_rank(ULTIMATE, &points)).then_some(ULTIMATE)
+            .or_else(|| (0..4).find(|&ability| points[ability] == 0 && can_rank(ability, &points)))
+            .or_else(|| (0..4).find(|&ability| can_rank(ability, &points)));
+        if let Some(ability) = ability {
+            points[ability] += 1;
+        }
+    }
+    points
+}
+
+/// Checks that a champion's skill points could have been spent this way by a given level.
+pub fn validate(champion: &str, skill_points: &[u8; 4], level: u8) -> Result<(), String> {
+    skill_rules(champion).validate(skill_points, level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basic_abilities_unlock_every_other_level() {
+        let rules = skill_rules("Garen");
+        assert_eq!(rules.max_rank(0, 1), 1);
+        assert_eq!(rules.max_rank(0, 2), 1);
+        assert_eq!(rules.max_rank(0, 3), 2);
+        assert_eq!(rules.max_rank(0, 9), 5);
+        assert_eq!(rules.max_rank(0, 18), 5);
+    }
+
+    #[test]
+    fn ultimate_unlocks_at_six_eleven_and_sixteen() {
+        let rules = skill_rules("Garen");
+        assert_eq!(rules.max_rank(ULTIMATE, 5), 0);
+        assert_eq!(rules.max_rank(ULTIMATE, 6), 1);
+        assert_eq!(rules.max_rank(ULTIMATE, 15), 2);
+        assert_eq!(rules.max_rank(ULTIMATE, 16), 3);
+    }
+
+    #[test]
+    fn validation_checks_total_and_ranks() {
+        assert!(validate("Garen", &[5, 5, 5, 3], 18).is_ok());
+        assert!(validate("Garen", &[1, 1, 1, 0], 2).is_err());
+        assert!(validate("Garen", &[2, 0, 0, 0], 2).is_err());
+        assert!(validate("Garen", &[1, 1, 1, 1], 5).is_err());
+        assert!(validate("Garen", &[6, 5, 5, 2], 18).is_err());
+    }
+
+    #[test]
+    fn default_skill_points_are_valid() {
+        assert_eq!(default_skill_points("Garen", 3), [1, 1, 1, 0]);
+        assert_eq!(default_skill_points("Garen", 6), [3, 1, 1, 1]);
+        assert_eq!(default_skill_points("Garen", 18), [5, 5, 5, 3]);
+        for champion in ["Jayce", "Elise", "Udyr"] {
+            for level in 1..=18 {
+                assert!(validate(champion, &default_skill_points(champion, level), level).is_ok());
+            }
+        }
+        assert_eq!(default_skill_points("Udyr", 18).iter().sum::<u8>(), 18);
+    }
+
+    #[test]
+    fn champion_exceptions() {
+        assert!(validate("Jayce", &[6, 6, 6, 0], 18).is_ok());
+        assert!(validate("Jayce", &[1, 0, 0, 1], 6).is_err());
+        assert!(validate("Elise", &[0, 0, 0, 1], 1).is_ok());
+        assert!(validate("Nidalee", &[5, 5, 4, 4], 18).is_ok());
+        assert!(validate("Udyr", &[6, 6, 3, 3], 18).is_ok());
+        assert!(validate("Udyr", &[0, 0, 0, 1], 1).is_ok());
+    }
+}
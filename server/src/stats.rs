@@ -5,11 +5,15 @@
  *
  * Description: structs, implementations, and utility functions dealing with stats
  */
-use serde::{Deserialize, Serialize};
+use serde::de::{self, IgnoredAny, MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Sub};
 
 /// stores fields of an individual statistic
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
 pub struct Stat {
     pub flat: f64,
     pub percent: f64,
@@ -18,31 +22,117 @@ pub struct Stat {
     pub percent_bonus: f64,
 }
 
-/// stores all statistics as Stat structs
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// whether a stat is displayed as a plain number or as a percent
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
+pub enum StatUnit {
+    Flat,
+    Percent,
+}
+
+/// Declares every stat kind along with its data key and unit. Adding a stat only takes a new
+/// line here; storage, iteration, arithmetic and serialization all follow from this list.
+macro_rules! stat_kinds {
+    ($($kind:ident => $key:literal, $unit:ident;)*) => {
+        /// identifies an individual statistic
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum StatKind {
+            $($kind),*
+        }
+
+        impl StatKind {
+            /// number of stat kinds
+            pub const COUNT: usize = [$($key),*].len();
+            /// every stat kind in storage order
+            pub const ALL: [StatKind; StatKind::COUNT] = [$(StatKind::$kind),*];
+
+            /// Returns the key used for the stat in the Meraki data and in serialized stats.
+            pub fn key(self) -> &'static str {
+                match self {
+                    $(StatKind::$kind => $key),*
+                }
+            }
+
+            /// Returns whether the stat is a plain number or a percent.
+            pub fn unit(self) -> StatUnit {
+                match self {
+                    $(StatKind::$kind => StatUnit::$unit),*
+                }
+            }
+        }
+    };
+}
+
+stat_kinds! {
+    AbilityPower => "abilityPower", Flat;
+    Armor => "armor", Flat;
+    ArmorPenetration => "armorPenetration", Percent;
+    AttackDamage => "attackDamage", Flat;
+    AttackSpeed => "attackSpeed", Flat;
+    // scales bonus attack speed, which is not always equal to base attack speed
+    AttackSpeedRatio => "attackSpeedRatio", Flat;
+    CooldownReduction => "cooldownReduction", Percent;
+    CriticalStrikeChance => "criticalStrikeChance", Percent;
+    // damage dealt by critical strikes as a percent of attack damage, 175 for most champions
+    CriticalStrikeDamage => "criticalStrikeDamage", Percent;
+    // multiplies critical strike damage for champions such as Yasuo and Jhin
+    CriticalStrikeDamageModifier => "criticalStrikeDamageModifier", Flat;
+    GoldPer10 => "goldPer10", Flat;
+    HealAndShieldPower => "healAndShieldPower", Percent;
+    Health => "health", Flat;
+    HealthRegen => "healthRegen", Flat;
+    Lethality => "lethality", Flat;
+    Lifesteal => "lifesteal", Percent;
+    // like lifesteal, but heals from all physical damage rather than only attacks
+    PhysicalVamp => "physicalVamp", Percent;
+    MagicPenetration => "magicPenetration", Flat;
+    MagicPenetrationPercent => "magicPenetrationPercent", Percent;
+    MagicResistance => "magicResistance", Flat;
+    Mana => "mana", Flat;
+    ManaRegen => "manaRegen", Flat;
+    Movespeed => "movespeed", Flat;
+    AbilityHaste => "abilityHaste", Flat;
+    Omnivamp => "omnivamp", Percent;
+    Tenacity => "tenacity", Percent;
+    SlowResistance => "slowResistance", Percent;
+    AttackRange => "attackRange", Flat;
+    // range at which the champion automatically acquires targets
+    AcquisitionRadius => "acquisitionRadius", Flat;
+    // adaptive force becomes bonus attack damage or ability power, whichever is higher
+    AdaptiveForce => "adaptiveForce", Flat;
+    Shield => "shield", Flat;
+}
+
+impl StatKind {
+    /// Looks up a stat kind by its data key.
+    pub fn from_key(key: &str) -> Option<StatKind> {
+        StatKind::ALL.into_iter().find(|kind| kind.key() == key)
+    }
+
+    /// Returns whether the percent field of the stat scales the stat's total, as with Rabadon's
+    /// Deathcap, rather than being the stat itself. Attack speed has its own rules.
+    pub fn percent_scales_total(self) -> bool {
+        self.unit() == StatUnit::Flat && self != StatKind::AttackSpeed
+    }
+}
+
+impl Serialize for StatKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.key())
+    }
+}
+
+impl<'de> Deserialize<'de> for StatKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = String::deserialize(deserializer)?;
+        StatKind::from_key(&key).ok_or_else(|| de::Error::custom(format!("unknown stat '{}'", key)))
+    }
+}
+
+/// stores all statistics as Stat structs, indexed by StatKind
+#[derive(Debug, Clone, PartialEq)]
 pub struct Stats {
-    pub ability_power: Stat,
-    pub armor: Stat,
-    pub armor_penetration: Stat,
-    pub attack_damage: Stat,
-    pub attack_speed: Stat,
-    pub cooldown_reduction: Stat,
-    pub critical_strike_chance: Stat,
-    pub gold_per_10: Stat,
-    pub heal_and_shield_power: Stat,
-    pub health: Stat,
-    pub health_regen: Stat,
-    pub lethality: Stat,
-    pub lifesteal: Stat,
-    pub magic_penetration: Stat,
-    pub magic_resistance: Stat,
-    pub mana: Stat,
-    pub mana_regen: Stat,
-    pub movespeed: Stat,
-    pub ability_haste: Stat,
-    pub omnivamp: Stat,
-    pub tenacity: Stat,
+    stats: [Stat; StatKind::COUNT],
 }
 
 impl Default for Stat {
@@ -62,15 +152,11 @@ impl Stat {
     }
 }
 
-/// Adds two individual stats together.
-pub fn add(stat1: &Stat, stat2: &Stat) -> Stat {
-    Stat {
-        flat: stat1.flat + stat2.flat,
-        percent: stat1.percent + stat2.percent,
-        per_level: stat1.per_level + stat2.per_level,
-        percent_base: stat1.percent_base + stat2.percent_base,
-        percent_bonus: stat1.percent_bonus + stat2.percent_bonus,
-    }
+/// Returns the multiplier applied to per level growth at a given level. Levels are clamped to 1
+/// through 18, and level 1 has no growth.
+pub fn growth(level: u8) -> f64 {
+    let gained = level.clamp(1, 18) as f64 - 1.0;
+    gained * (0.7025 + 0.0175 * gained)
 }
 
 impl Default for Stats {
@@ -81,63 +167,412 @@ impl Default for Stats {
 impl Stats {
     pub fn new() -> Self {
         Stats {
-            ability_power: Stat::new(),
-            armor: Stat::new(),
-            armor_penetration: Stat::new(),
-            attack_damage: Stat::new(),
-            attack_speed: Stat::new(),
-            cooldown_reduction: Stat::new(),
-            critical_strike_chance: Stat::new(),
-            gold_per_10: Stat::new(),
-            heal_and_shield_power: Stat::new(),
-            health: Stat::new(),
-            health_regen: Stat::new(),
-            lethality: Stat::new(),
-            lifesteal: Stat::new(),
-            magic_penetration: Stat::new(),
-            magic_resistance: Stat::new(),
-            mana: Stat::new(),
-            mana_regen: Stat::new(),
-            movespeed: Stat::new(),
-            ability_haste: Stat::new(),
-            omnivamp: Stat::new(),
-            tenacity: Stat::new(),
+            stats: [Stat::new(); StatKind::COUNT],
         }
     }
-    pub fn get_stats(&self) -> Stats {
-        self.clone() 
+
+    /// Iterates over every stat along with its kind.
+    pub fn iter(&self) -> impl Iterator<Item = (StatKind, &Stat)> {
+        StatKind::ALL.into_iter().zip(self.stats.iter())
     }
 
-    pub fn create_stats() -> Stats {
-        Stats::new()
+    /// Iterates mutably over every stat along with its kind.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (StatKind, &mut Stat)> {
+        StatKind::ALL.into_iter().zip(self.stats.iter_mut())
     }
 
-    /// Adds all stats together.
-    pub fn add_stats(stats1: &Stats, stats2: &Stats) -> Stats {
+    /// Applies a function to every pair of stats from two sets.
+    fn zip_with(&self, other: &Stats, f: impl Fn(&Stat, &Stat) -> Stat) -> Stats {
         let mut merged = Stats::new();
-        
-        merged.ability_power = add(&stats1.ability_power, &stats2.ability_power);
-        merged.armor = add(&stats1.armor, &stats2.armor);
-        merged.armor_penetration = add(&stats1.armor_penetration, &stats2.armor_penetration);
-        merged.attack_damage = add(&stats1.attack_damage, &stats2.attack_damage);
-        merged.attack_speed = add(&stats1.attack_speed, &stats2.attack_speed);
-        merged.cooldown_reduction = add(&stats1.cooldown_reduction, &stats2.cooldown_reduction);
-        merged.critical_strike_chance = add(&stats1.critical_strike_chance, &stats2.critical_strike_chance);
-        merged.gold_per_10 = add(&stats1.gold_per_10, &stats2.gold_per_10);
-        merged.heal_and_shield_power = add(&stats1.heal_and_shield_power, &stats2.heal_and_shield_power);
-        merged.health = add(&stats1.health, &stats2.health);
-        merged.health_regen = add(&stats1.health_regen, &stats2.health_regen);
-        merged.lethality = add(&stats1.lethality, &stats2.lethality);
-        merged.lifesteal = add(&stats1.lifesteal, &stats2.lifesteal);
-        merged.magic_penetration = add(&stats1.magic_penetration, &stats2.magic_penetration);
-        merged.mana = add(&stats1.mana, &stats2.mana);
-        merged.mana_regen = add(&stats1.mana_regen, &stats2.mana_regen);
-        merged.movespeed = add(&stats1.movespeed, &stats2.movespeed);
-        merged.ability_haste = add(&stats1.ability_haste, &stats2.ability_haste);
-        merged.omnivamp = add(&stats1.omnivamp, &stats2.omnivamp);
-        merged.tenacity = add(&stats1.tenacity, &stats2.tenacity);
-
+        for (kind, stat) in merged.iter_mut() {
+            *stat = f(&self[kind], &other[kind]);
+        }
         merged
     }
+}
+
+impl Index<StatKind> for Stats {
+    type Output = Stat;
+
+    fn index(&self, kind: StatKind) -> &Stat {
+        &self.stats[kind as usize]
+    }
+}
+
+impl IndexMut<StatKind> for Stats {
+    fn index_mut(&mut self, kind: StatKind) -> &mut Stat {
+        &mut self.stats[kind as usize]
+    }
+}
+
+impl Add for Stat {
+    type Output = Stat;
+
+    fn add(self, other: Stat) -> Stat {
+        Stat {
+            flat: self.flat + other.flat,
+            percent: self.percent + other.percent,
+            per_level: self.per_level + other.per_level,
+            percent_base: self.percent_base + other.percent_base,
+            percent_bonus: self.percent_bonus + other.percent_bonus,
+        }
+    }
+}
+
+impl Sub for Stat {
+    type Output = Stat;
+
+    fn sub(self, other: Stat) -> Stat {
+        self + other * -1.0
+    }
+}
+
+impl Mul<f64> for Stat {
+    type Output = Stat;
+
+    fn mul(self, scale: f64) -> Stat {
+        Stat {
+            flat: self.flat * scale,
+            percent: self.percent * scale,
+            per_level: self.per_level * scale,
+            percent_base: self.percent_base * scale,
+            percent_bonus: self.percent_bonus * scale,
+        }
+    }
+}
+
+impl Add<&Stats> for &Stats {
+    type Output = Stats;
+
+    fn add(self, other: &Stats) -> Stats {
+        self.zip_with(other, |a, b| *a + *b)
+    }
+}
+
+impl Add for Stats {
+    type Output = Stats;
+
+    fn add(self, other: Stats) -> Stats {
+        &self + &other
+    }
+}
+
+impl AddAssign<&Stats> for Stats {
+    fn add_assign(&mut self, other: &Stats) {
+        *self = &*self + other;
+    }
+}
+
+impl Sub<&Stats> for &Stats {
+    type Output = Stats;
+
+    fn sub(self, other: &Stats) -> Stats {
+        self.zip_with(other, |a, b| *a - *b)
+    }
+}
+
+impl Sub for Stats {
+    type Output = Stats;
+
+    fn sub(self, other: Stats) -> Stats {
+        &self - &other
+    }
+}
+
+impl Mul<f64> for &Stats {
+    type Output = Stats;
+
+    fn mul(self, scale: f64) -> Stats {
+        let mut scaled = self.clone();
+        for (_, stat) in scaled.iter_mut() {
+            *stat = *stat * scale;
+        }
+        scaled
+    }
+}
+
+impl Mul<f64> for Stats {
+    type Output = Stats;
+
+    fn mul(self, scale: f64) -> Stats {
+        &self * scale
+    }
+}
+
+/// Serializes stats as a map from stat key to stat, matching the Meraki data layout.
+impl Serialize for Stats {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(StatKind::COUNT))?;
+        for (kind, stat) in self.iter() {
+            map.serialize_entry(kind.key(), stat)?;
+        }
+        map.end()
+    }
+}
+
+/// Deserializes stats from a map of stat keys. Unknown keys are ignored and missing stats are
+/// left at zero, so item data with extra or fewer stats can still be read.
+impl<'de> Deserialize<'de> for Stats {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct StatsVisitor;
 
+        impl<'de> Visitor<'de> for StatsVisitor {
+            type Value = Stats;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of stats")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Stats, A::Error> {
+                let mut stats = Stats::new();
+                while let Some(key) = access.next_key::<String>()? {
+                    match StatKind::from_key(&key) {
+                        Some(kind) => stats[kind] = access.next_value()?,
+                        None => {
+                            access.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                }
+                Ok(stats)
+            }
+        }
+
+        deserializer.deserialize_map(StatsVisitor)
+    }
+}
+
+/// stores the final value of every stat after levels, items and passives are applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedStats {
+    values: [f64; StatKind::COUNT],
+}
+
+impl Default for ResolvedStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl ResolvedStats {
+    pub fn new() -> Self {
+        ResolvedStats {
+            values: [0.0; StatKind::COUNT],
+        }
+    }
+
+    /// Iterates over every resolved value along with its kind.
+    pub fn iter(&self) -> impl Iterator<Item = (StatKind, f64)> + '_ {
+        StatKind::ALL.into_iter().zip(self.values.iter().copied())
+    }
+}
+
+impl Index<StatKind> for ResolvedStats {
+    type Output = f64;
+
+    fn index(&self, kind: StatKind) -> &f64 {
+        &self.values[kind as usize]
+    }
+}
+
+impl IndexMut<StatKind> for ResolvedStats {
+    fn index_mut(&mut self, kind: StatKind) -> &mut f64 {
+        &mut self.values[kind as usize]
+    }
+}
+
+impl Sub<&ResolvedStats> for &ResolvedStats {
+    type Output = ResolvedStats;
+
+    fn sub(self, other: &ResolvedStats) -> ResolvedStats {
+        let mut result = self.clone();
+        for (value, other) in result.values.iter_mut().zip(other.values) {
+            *value -= other;
+        }
+        result
+    }
+}
+
+impl Serialize for ResolvedStats {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(StatKind::COUNT))?;
+        for (kind, value) in self.iter() {
+            map.serialize_entry(kind.key(), &value)?;
+        }
+        map.end()
+    }
+}
+
+/// Attack speed is capped at this value unless the champion is an exception.
+pub const ATTACK_SPEED_CAP: f64 = 2.5;
+
+/// stores attack speed the way the game displays it
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct AttackSpeed {
+    /// attacks per second at level 1 without bonuses
+    pub base: f64,
+    /// bonus attack speed from levels and items, as a percent
+    pub bonus: f64,
+    /// attacks per second after the ratio and cap are applied
+    pub total: f64,
+}
+
+/// Champion-specific deviations from the usual attack speed rules.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AttackSpeedRule {
+    /// attacks per second never go above this, which is infinite for uncapped champions
+    pub cap: f64,
+    /// whether bonus attack speed from items increases attacks per second
+    pub item_bonus_applies: bool,
+    /// percent of attack damage gained for every percent of bonus attack speed
+    pub attack_damage_per_bonus: f64,
+}
+
+/// rule for every champion missing from ATTACK_SPEED_RULES
+const DEFAULT_ATTACK_SPEED_RULE: AttackSpeedRule = AttackSpeedRule {
+    cap: ATTACK_SPEED_CAP,
+    item_bonus_applies: true,
+    attack_damage_per_bonus: 0.0,
+};
+
+/// champions whose attack speed does not follow the usual rules
+const ATTACK_SPEED_RULES: &[(&str, AttackSpeedRule)] = &[
+    // Jhin converts bonus attack speed into attack damage instead
+    ("Jhin", AttackSpeedRule { item_bonus_applies: false, attack_damage_per_bonus: 0.3, ..DEFAULT_ATTACK_SPEED_RULE }),
+];
+
+/// Looks up the attack speed rule for a champion.
+pub fn attack_speed_rule(champion: &str) -> AttackSpeedRule {
+    ATTACK_SPEED_RULES
+        .iter()
+        .find(|(name, _)| *name == champion)
+        .map_or(DEFAULT_ATTACK_SPEED_RULE, |(_, rule)| *rule)
+}
+
+/// Returns the ratio that scales bonus attack speed, falling back to base attack speed for
+/// champions without one.
+pub fn attack_speed_ratio(base: &Stats) -> f64 {
+    if base[StatKind::AttackSpeedRatio].flat != 0.0 {
+        base[StatKind::AttackSpeedRatio].flat
+    } else {
+        base[StatKind::AttackSpeed].flat
+    }
+}
+
+/// Computes attack speed from the champion's base stats and the bonus stats granted by items.
+/// Per level attack speed is bonus attack speed, so it is scaled by the attack speed ratio
+/// rather than base attack speed.
+pub fn attack_speed(base: &Stats, bonus: &Stats, level: u8, champion: &str) -> AttackSpeed {
+    let rule = attack_speed_rule(champion);
+    let base_attack_speed = base[StatKind::AttackSpeed].flat;
+    let ratio = attack_speed_ratio(base);
+
+    let level_bonus = base[StatKind::AttackSpeed].per_level * growth(level);
+    let item_bonus = base[StatKind::AttackSpeed].percent + bonus[StatKind::AttackSpeed].percent;
+    let bonus_percent = level_bonus + item_bonus;
+
+    let applied_percent = if rule.item_bonus_applies { bonus_percent } else { level_bonus };
+    let total = (base_attack_speed + ratio * applied_percent / 100.0).min(rule.cap);
+
+    AttackSpeed {
+        base: base_attack_speed,
+        bonus: bonus_percent,
+        total,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adding_covers_every_stat() {
+        let mut stats = Stats::new();
+        for (_, stat) in stats.iter_mut() {
+            stat.flat = 1.0;
+        }
+        let merged = &stats + &stats;
+        assert!(merged.iter().all(|(_, stat)| stat.flat == 2.0));
+        assert_eq!(merged[StatKind::MagicResistance].flat, 2.0);
+        assert_eq!(&merged - &stats, stats);
+        assert_eq!(&stats * 2.0, merged);
+    }
+
+    #[test]
+    fn deserializes_meraki_item_stats() {
+        let stats: Stats = serde_json::from_str(r#"{
+            "magicResistance": { "flat": 40.0, "percent": 0.0, "perLevel": 0.0, "percentPerLevel": 0.0, "percentBase": 0.0, "percentBonus": 0.0 },
+            "armor": { "flat": 20.0 },
+            "somethingNew": { "flat": 1.0 }
+        }"#).unwrap();
+        assert_eq!(stats[StatKind::MagicResistance].flat, 40.0);
+        assert_eq!(stats[StatKind::Armor].flat, 20.0);
+        assert_eq!(stats[StatKind::Health], Stat::new());
+    }
+
+    #[test]
+    fn serialization_round_trips() {
+        let mut stats = Stats::new();
+        stats[StatKind::GoldPer10].flat = 3.0;
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["goldPer10"]["flat"], 3.0);
+        assert_eq!(serde_json::from_value::<Stats>(json).unwrap(), stats);
+    }
+
+    #[test]
+    fn keys_are_unique() {
+        for kind in StatKind::ALL {
+            assert_eq!(StatKind::from_key(kind.key()), Some(kind));
+        }
+    }
+
+    fn champion(attack_speed: f64, ratio: f64, per_level: f64) -> Stats {
+        let mut stats = Stats::new();
+        stats[StatKind::AttackSpeed].flat = attack_speed;
+        stats[StatKind::AttackSpeed].per_level = per_level;
+        stats[StatKind::AttackSpeedRatio].flat = ratio;
+        stats
+    }
+
+    fn items(bonus_percent: f64) -> Stats {
+        let mut stats = Stats::new();
+        stats[StatKind::AttackSpeed].percent = bonus_percent;
+        stats
+    }
+
+    #[test]
+    fn bonus_attack_speed_scales_with_ratio() {
+        let caitlyn = champion(0.681, 0.568, 4.0);
+        let attack_speed = attack_speed(&caitlyn, &items(25.0), 1, "Caitlyn");
+        assert_eq!(attack_speed.base, 0.681);
+        assert_eq!(attack_speed.bonus, 25.0);
+        assert!((attack_speed.total - 0.823).abs() < 1e-9);
+    }
+
+    #[test]
+    fn level_growth_is_bonus_attack_speed() {
+        let caitlyn = champion(0.681, 0.568, 4.0);
+        let attack_speed = attack_speed(&caitlyn, &Stats::new(), 18, "Caitlyn");
+        assert!((attack_speed.bonus - 68.0).abs() < 1e-9);
+        assert!((attack_speed.total - (0.681 + 0.568 * 0.68)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn attack_speed_is_capped() {
+        let jinx = champion(0.625, 0.625, 1.0);
+        assert_eq!(attack_speed(&jinx, &items(400.0), 18, "Jinx").total, ATTACK_SPEED_CAP);
+    }
+
+    #[test]
+    fn rules_fall_back_to_the_default() {
+        assert_eq!(attack_speed_rule("Caitlyn"), DEFAULT_ATTACK_SPEED_RULE);
+        assert_eq!(attack_speed_rule("Jhin").cap, ATTACK_SPEED_CAP);
+        assert!(!attack_speed_rule("Jhin").item_bonus_applies);
+    }
+
+    #[test]
+    fn jhin_ignores_item_attack_speed() {
+        let jhin = champion(0.625, 0.625, 0.0);
+        let attack_speed = attack_speed(&jhin, &items(50.0), 1, "Jhin");
+        assert_eq!(attack_speed.bonus, 50.0);
+        assert_eq!(attack_speed.total, 0.625);
+    }
 }
@@ -0,0 +1,313 @@
+/*
+ * File: storage.rs
+ *
+ * Copyright (c) 2025 Alan Fung
+ *
+ * Description: saves named builds to disk so they survive a restart
+ */
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+use crate::buildcode::BuildCode;
+use crate::gamedata::SharedGameData;
+use crate::session::Sessions;
+
+const BUILDS_PATH: &str = "public/builds.json";
+
+/// stores a build saved under a name, tagged with the patch it was saved on. Only the choices
+/// that make up the build are kept, so base stats come from the game data when it is loaded.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedBuild {
+    pub name: String,
+    pub patch: Option<String>,
+    /// seconds since the unix epoch
+    pub saved_at: u64,
+    #[serde(flatten)]
+    pub build: BuildCode,
+}
+
+/// ways a build operation can fail
+#[derive(Debug, PartialEq)]
+pub enum BuildError {
+    NotFound(String),
+    AlreadyExists(String),
+    InvalidName,
+    Io(String),
+}
+
+impl BuildError {
+    fn response(&self) -> HttpResponse {
+        match self {
+            BuildError::NotFound(name) => HttpResponse::NotFound().body(format!("Build '{}' not found", name)),
+            BuildError::AlreadyExists(name) => HttpResponse::Conflict().body(format!("Build '{}' already exists", name)),
+            BuildError::InvalidName => HttpResponse::BadRequest().body("Build names cannot be empty"),
+            BuildError::Io(err) => HttpResponse::InternalServerError().body(err.clone()),
+        }
+    }
+}
+
+/// stores every saved build by name, writing the whole store to a JSON file after each change
+pub struct BuildStore {
+    path: PathBuf,
+    builds: Mutex<BTreeMap<String, SavedBuild>>,
+}
+
+impl BuildStore {
+    /// Opens the build store in the data directory.
+    pub fn open_default() -> Result<Self, String> {
+        Self::open(BUILDS_PATH)
+    }
+
+    /// Opens the build store at a given path, starting empty if the file does not exist yet.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let builds = if path.exists() {
+            let data = fs::read_to_string(&path).map_err(|e| format!("Failed to read builds: {}", e))?;
+            serde_json::from_str(&data).map_err(|e| format!("Failed to parse builds: {}", e))?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(BuildStore {
+            path,
+            builds: Mutex::new(builds),
+        })
+    }
+
+    /// Writes builds to a temporary file first so a failed write cannot corrupt the store.
+    fn persist(&self, builds: &BTreeMap<String, SavedBuild>) -> Result<(), BuildError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| BuildError::Io(format!("Failed to create directory: {}", e)))?;
+        }
+        let data = serde_json::to_string_pretty(builds).map_err(|e| BuildError::Io(e.to_string()))?;
+        let temp = self.path.with_extension("json.tmp");
+        fs::write(&temp, data).map_err(|e| BuildError::Io(format!("Failed to write builds: {}", e)))?;
+        fs::rename(&temp, &self.path).map_err(|e| BuildError::Io(format!("Failed to write builds: {}", e)))
+    }
+
+    /// Lists every saved build, optionally only those for a given champion.
+    pub async fn list(&self, champion: Option<&str>) -> Vec<SavedBuild> {
+        let builds = self.builds.lock().await;
+        builds.values()
+            .filter(|saved| champion.is_none_or(|champion| saved.build.champion.eq_ignore_ascii_case(champion)))
+            .cloned()
+            .collect()
+    }
+
+    /// Retrieves a saved build.
+    pub async fn get(&self, name: &str) -> Result<SavedBuild, BuildError> {
+        let builds = self.builds.lock().await;
+        builds.get(name).cloned().ok_or_else(|| BuildError::NotFound(name.to_string()))
+    }
+
+    /// Saves a build under a name, replacing any build with the same name.
+    pub async fn save(&self, name: &str, build: BuildCode, patch: Option<String>) -> Result<SavedBuild, BuildError> {
+        if name.trim().is_empty() {
+            return Err(BuildError::InvalidName);
+        }
+
+        let build = SavedBuild {
+            name: name.to_string(),
+            patch,
+            saved_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            build,
+        };
+
+        let mut builds = self.builds.lock().await;
+        let mut updated = builds.clone();
+        updated.insert(name.to_string(), build.clone());
+        self.persist(&updated)?;
+        *builds = updated;
+        Ok(build)
+    }
+
+    /// Copies a build to a new name, keeping the original when duplicating.
+    async fn copy(&self, name: &str, new_name: &str, keep_original: bool) -> Result<SavedBuild, BuildError> {
+        if new_name.trim().is_empty() {
+            return Err(BuildError::InvalidName);
+        }
+
+        let mut builds = self.builds.lock().await;
+        if builds.contains_key(new_name) {
+            return Err(BuildError::AlreadyExists(new_name.to_string()));
+        }
+
+        let mut updated = builds.clone();
+        let mut build = if keep_original {
+            updated.get(name).cloned()
+        } else {
+            updated.remove(name)
+        }
+        .ok_or_else(|| BuildError::NotFound(name.to_string()))?;
+
+        build.name = new_name.to_string();
+        updated.insert(new_name.to_string(), build.clone());
+        self.persist(&updated)?;
+        *builds = updated;
+        Ok(build)
+    }
+
+    /// Renames a saved build.
+    pub async fn rename(&self, name: &str, new_name: &str) -> Result<SavedBuild, BuildError> {
+        self.copy(name, new_name, false).await
+    }
+
+    /// Saves a copy of a build under a new name.
+    pub async fn duplicate(&self, name: &str, new_name: &str) -> Result<SavedBuild, BuildError> {
+        self.copy(name, new_name, true).await
+    }
+
+    /// Deletes a saved build.
+    pub async fn delete(&self, name: &str) -> Result<(), BuildError> {
+        let mut builds = self.builds.lock().await;
+        let mut updated = builds.clone();
+        updated.remove(name).ok_or_else(|| BuildError::NotFound(name.to_string()))?;
+        self.persist(&updated)?;
+        *builds = updated;
+        Ok(())
+    }
+}
+
+/// This structure is only useful for reading the query of list_builds.
+#[derive(Deserialize)]
+pub struct BuildFilter {
+    champion: Option<String>,
+}
+
+/// Lists saved builds, optionally filtered by champion with ?champion=name.
+pub async fn list_builds(builds: web::Data<BuildStore>, query: web::Query<BuildFilter>) -> impl actix_web::Responder {
+    HttpResponse::Ok().json(builds.list(query.champion.as_deref()).await)
+}
+
+/// Saves the session's player under a given name, tagged with the patch of the game data.
+pub async fn save_build(req: HttpRequest, sessions: web::Data<Sessions>, game_data: web::Data<SharedGameData>, builds: web::Data<BuildStore>, name: web::Path<String>) -> impl actix_web::Responder {
+    let session = sessions.for_request(&req).await;
+    let session = session.lock().await;
+
+    let patch = game_data.get().patch().map(str::to_string);
+    match builds.save(&name, BuildCode::from_player(&session.player), patch).await {
+        Ok(build) => HttpResponse::Ok().json(build),
+        Err(err) => err.response(),
+    }
+}
+
+/// Replaces the session's player with a saved build, with base stats from the current game data.
+pub async fn load_build(req: HttpRequest, sessions: web::Data<Sessions>, game_data: web::Data<SharedGameData>, builds: web::Data<BuildStore>, name: web::Path<String>) -> impl actix_web::Responder {
+    let saved = match builds.get(&name).await {
+        Ok(saved) => saved,
+        Err(err) => return err.response(),
+    };
+    let player = match saved.build.to_player(&game_data.get()) {
+        Ok(player) => player,
+        Err(err) => return HttpResponse::BadRequest().body(err),
+    };
+
+    let session = sessions.for_request(&req).await;
+    let mut session = session.lock().await;
+    session.edit(format!("Load build {}", saved.name), |current| *current = player);
+    HttpResponse::Ok().body(format!("Successfully loaded build {}", saved.name))
+}
+
+/// Renames a saved build.
+pub async fn rename_build(builds: web::Data<BuildStore>, path: web::Path<(String, String)>) -> impl actix_web::Responder {
+    let (name, new_name) = path.into_inner();
+    match builds.rename(&name, &new_name).await {
+        Ok(build) => HttpResponse::Ok().json(build),
+        Err(err) => err.response(),
+    }
+}
+
+/// Saves a copy of a build under a new name.
+pub async fn duplicate_build(builds: web::Data<BuildStore>, path: web::Path<(String, String)>) -> impl actix_web::Responder {
+    let (name, new_name) = path.into_inner();
+    match builds.duplicate(&name, &new_name).await {
+        Ok(build) => HttpResponse::Ok().json(build),
+        Err(err) => err.response(),
+    }
+}
+
+/// Deletes a saved build.
+pub async fn delete_build(builds: web::Data<BuildStore>, name: web::Path<String>) -> impl actix_web::Responder {
+    match builds.delete(&name).await {
+        Ok(()) => HttpResponse::Ok().body(format!("Successfully deleted build {}", name)),
+        Err(err) => err.response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamedata::tests::sample;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("itemizer_{}_{}.json", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn garen() -> BuildCode {
+        BuildCode {
+            champion: "Garen".to_string(),
+            level: 6,
+            skill_points: [3, 1, 1, 1],
+            items: [3031, 0, 0, 0, 0, 0],
+        }
+    }
+
+    #[actix_web::test]
+    async fn builds_survive_reopening() {
+        let path = temp_path("reopen");
+        let store = BuildStore::open(&path).unwrap();
+        store.save("bruiser", garen(), Some("14.10".to_string())).await.unwrap();
+
+        let reopened = BuildStore::open(&path).unwrap();
+        let saved = reopened.get("bruiser").await.unwrap();
+        assert_eq!(saved.patch.as_deref(), Some("14.10"));
+        assert_eq!(saved.build, garen());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[actix_web::test]
+    async fn only_build_choices_are_stored() {
+        let path = temp_path("choices");
+        let store = BuildStore::open(&path).unwrap();
+        store.save("bruiser", garen(), None).await.unwrap();
+
+        let stored: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let mut keys: Vec<&String> = stored["bruiser"].as_object().unwrap().keys().collect();
+        keys.sort();
+        assert_eq!(keys, ["champion", "items", "level", "name", "patch", "savedAt", "skillPoints"]);
+
+        let data = sample();
+        let player = store.get("bruiser").await.unwrap().build.to_player(&data).unwrap();
+        assert_eq!(player.base_stats, *data.champion("Garen").unwrap());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[actix_web::test]
+    async fn rename_duplicate_and_delete() {
+        let path = temp_path("manage");
+        let store = BuildStore::open(&path).unwrap();
+        store.save("a", garen(), None).await.unwrap();
+        store.save("b", BuildCode { champion: "Annie".to_string(), ..garen() }, None).await.unwrap();
+
+        assert_eq!(store.rename("a", "b").await.unwrap_err(), BuildError::AlreadyExists("b".to_string()));
+        store.rename("a", "c").await.unwrap();
+        assert_eq!(store.get("a").await.unwrap_err(), BuildError::NotFound("a".to_string()));
+
+        store.duplicate("c", "d").await.unwrap();
+        assert_eq!(store.get("d").await.unwrap().build.items[0], 3031);
+        assert_eq!(store.list(Some("garen")).await.len(), 2);
+
+        store.delete("c").await.unwrap();
+        assert_eq!(store.delete("c").await.unwrap_err(), BuildError::NotFound("c".to_string()));
+        assert_eq!(BuildStore::open(&path).unwrap().list(None).await.len(), 2);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,359 @@
+/*
+ * File: timeline.rs
+ *
+ * Copyright (c) 2025 Alan Fung
+ *
+ * Description: models gold, experience and purchases over a game, to show what a build looks like
+ * at each minute
+ */
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::breakdown::{self, Source, SourceKind};
+use crate::gamedata::{GameData, SharedGameData};
+use crate::player::MAX_LEVEL;
+use crate::stats::{ResolvedStats, StatKind};
+
+/// gold every player starts with
+const STARTING_GOLD: f64 = 500.0;
+/// seconds into the game when passive gold starts
+const PASSIVE_GOLD_START: f64 = 65.0;
+/// passive gold earned every 10 seconds
+const PASSIVE_GOLD_PER_10: f64 = 20.4;
+/// seconds into the game when the first minions reach lane, which starts farm and experience
+const FARM_START: f64 = 90.0;
+/// average gold of a minion across a wave, cannon minions included
+const MINION_GOLD: f64 = 21.0;
+/// rough gold earned every 10 seconds from a support item until its quest is complete
+const SUPPORT_GOLD_PER_10: f64 = 15.0;
+/// gold a support item earns before its quest is complete
+const SUPPORT_QUEST_GOLD: f64 = 500.0;
+/// longest game that can be modelled, in minutes
+const MAX_MINUTES: u32 = 60;
+/// most items a player can hold
+const SLOTS: usize = 6;
+
+/// describes a game to model and the assumptions made about it. Items are never sold, so an
+/// item that does not fit in a full inventory is skipped.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GamePlan {
+    pub champion: String,
+    /// items and components in the order they are bought
+    #[serde(default)]
+    pub buy_order: Vec<u16>,
+    #[serde(default = "default_minutes")]
+    pub minutes: u32,
+    #[serde(default = "default_cs_per_minute")]
+    pub cs_per_minute: f64,
+    #[serde(default = "default_experience_per_minute")]
+    pub experience_per_minute: f64,
+    /// whether the player holds a support item, which earns gold until its quest is complete
+    #[serde(default)]
+    pub support: bool,
+}
+
+fn default_minutes() -> u32 {
+    30
+}
+
+fn default_cs_per_minute() -> f64 {
+    7.0
+}
+
+fn default_experience_per_minute() -> f64 {
+    400.0
+}
+
+/// stores the state of the player at the end of a minute
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub minute: u32,
+    pub level: u8,
+    pub experience: f64,
+    /// gold not yet spent
+    pub gold: f64,
+    /// gold earned since the start of the game, starting gold included
+    pub earned: f64,
+    pub items: Vec<u16>,
+    /// items bought during this minute
+    pub bought: Vec<u16>,
+    /// items from the buy order skipped during this minute because the inventory was full
+    pub skipped: Vec<u16>,
+    pub stats: ResolvedStats,
+}
+
+/// Returns the total experience needed to reach a level.
+fn experience_to_reach(level: u8) -> f64 {
+    (1..level.min(MAX_LEVEL)).map(|level| 180.0 + 100.0 * level as f64).sum()
+}
+
+/// Returns the level reached with a given amount of experience.
+pub fn level_for(experience: f64) -> u8 {
+    (1..MAX_LEVEL).take_while(|&level| experience >= experience_to_reach(level + 1)).count() as u8 + 1
+}
+
+/// Takes the components of an item out of the inventory, looking for the components of any
+/// component that is not held, and returns the gold they are worth.
+fn held_components(data: &GameData, id: u16, inventory: &mut Vec<u16>) -> f64 {
+    let Some(item) = data.item(id) else { return 0.0 };
+
+    item.builds_from.iter()
+        .map(|&component| match inventory.iter().position(|&held| held == component) {
+            Some(slot) => {
+                inventory.remove(slot);
+                data.item(component).map_or(0.0, |component| component.gold as f64)
+            }
+            None => held_components(data, component, inventory),
+        })
+        .sum()
+}
+
+/// stores the gold, experience and inventory of the player as the game goes on
+struct State {
+    gold: f64,
+    earned: f64,
+    experience: f64,
+    support_earned: f64,
+    items: Vec<u16>,
+    next_purchase: usize,
+}
+
+impl State {
+    fn earn(&mut self, gold: f64) {
+        self.gold += gold;
+        self.earned += gold;
+    }
+
+    /// Buys items from the buy order in turn for as long as there is gold. Completing an item
+    /// uses up the components held in the inventory, which are taken off its price. Items that
+    /// would not fit in the inventory are skipped. Returns the items bought and skipped.
+    fn shop(&mut self, data: &GameData, plan: &GamePlan) -> Result<(Vec<u16>, Vec<u16>), String> {
+        let mut bought = Vec::new();
+        let mut skipped = Vec::new();
+
+        while let Some(&id) = plan.buy_order.get(self.next_purchase) {
+            let item = data.item(id).ok_or_else(|| format!("Unknown item {}", id))?;
+
+            let mut remaining = self.items.clone();
+            let cost = item.gold as f64 - held_components(data, id, &mut remaining);
+
+            if self.gold < cost {
+                break;
+            }
+            self.next_purchase += 1;
+            if remaining.len() >= SLOTS {
+                skipped.push(id);
+                continue;
+            }
+
+            self.gold -= cost;
+            remaining.push(id);
+            self.items = remaining;
+            bought.push(id);
+        }
+
+        Ok((bought, skipped))
+    }
+
+    /// Adds up the gold earned every 10 seconds from the items held.
+    fn item_gold_per_10(&self, data: &GameData) -> f64 {
+        self.items.iter()
+            .filter_map(|&id| data.item(id))
+            .map(|item| item.stats[StatKind::GoldPer10].flat)
+            .sum()
+    }
+}
+
+/// Models a game second by second, shopping and recording the player's state at the start of the
+/// game and at the end of each minute. Items are bought as soon as they can be afforded, as if
+/// the player could always reach the shop.
+pub fn simulate(data: &GameData, plan: &GamePlan) -> Result<Vec<Snapshot>, String> {
+    if plan.minutes > MAX_MINUTES {
+        return Err(format!("A game can be modelled for at most {} minutes", MAX_MINUTES));
+    }
+    if plan.cs_per_minute < 0.0 || plan.experience_per_minute < 0.0 {
+        return Err("Farm and experience rates cannot be negative".to_string());
+    }
+    let base_stats = data.champion(&plan.champion).ok_or_else(|| format!("Unknown champion {}", plan.champion))?;
+
+    let mut state = State {
+        gold: STARTING_GOLD,
+        earned: STARTING_GOLD,
+        experience: 0.0,
+        support_earned: 0.0,
+        items: Vec::new(),
+        next_purchase: 0,
+    };
+    let mut snapshots = Vec::new();
+
+    for second in 0..=plan.minutes * 60 {
+        let time = second as f64;
+        if second > 0 {
+            if time > PASSIVE_GOLD_START {
+                state.earn(PASSIVE_GOLD_PER_10 / 10.0);
+            }
+            if time > FARM_START {
+                state.earn(plan.cs_per_minute / 60.0 * MINION_GOLD);
+                state.experience += plan.experience_per_minute / 60.0;
+            }
+            state.earn(state.item_gold_per_10(data) / 10.0);
+            if plan.support && state.support_earned < SUPPORT_QUEST_GOLD {
+                let gold = (SUPPORT_GOLD_PER_10 / 10.0).min(SUPPORT_QUEST_GOLD - state.support_earned);
+                state.support_earned += gold;
+                state.earn(gold);
+            }
+        }
+
+        if second % 60 != 0 {
+            continue;
+        }
+        let minute = second / 60;
+        let (bought, skipped) = state.shop(data, plan)?;
+        let level = level_for(state.experience);
+
+        let mut sources = vec![Source {
+            kind: SourceKind::Champion,
+            id: plan.champion.clone(),
+            name: plan.champion.clone(),
+            stats: base_stats.clone(),
+        }];
+        sources.extend(data.item_sources(&state.items)?);
+
+        snapshots.push(Snapshot {
+            minute,
+            level,
+            experience: state.experience,
+            gold: state.gold,
+            earned: state.earned,
+            items: state.items.clone(),
+            bought,
+            skipped,
+            stats: breakdown::resolve(&sources, level, &plan.champion),
+        });
+    }
+
+    Ok(snapshots)
+}
+
+/// This structure is only useful for reading the query of game_timeline.
+#[derive(Deserialize)]
+pub struct TimelineQuery {
+    /// only return the state at this minute
+    #[serde(default)]
+    minute: Option<u32>,
+}
+
+/// Models the game described in the body, returning the player's state at every minute or at the
+/// minute given in the query.
+pub async fn game_timeline(game_data: web::Data<SharedGameData>, query: web::Query<TimelineQuery>, body: web::Json<GamePlan>) -> impl actix_web::Responder {
+    let plan = body.into_inner();
+    let snapshots = match simulate(&game_data.get(), &plan) {
+        Ok(snapshots) => snapshots,
+        Err(err) => return HttpResponse::BadRequest().body(err),
+    };
+
+    match query.minute {
+        Some(minute) => match snapshots.into_iter().find(|snapshot| snapshot.minute == minute) {
+            Some(snapshot) => HttpResponse::Ok().json(snapshot),
+            None => HttpResponse::BadRequest().body(format!("Minute {} is past the end of the game", minute)),
+        },
+        None => HttpResponse::Ok().json(snapshots),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamedata::tests::sample;
+
+    fn plan(buy_order: &[u16]) -> GamePlan {
+        GamePlan {
+            champion: "Garen".to_string(),
+            buy_order: buy_order.to_vec(),
+            minutes: 30,
+            cs_per_minute: 0.0,
+            experience_per_minute: 0.0,
+            support: false,
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn levels_follow_the_experience_curve() {
+        assert_eq!(level_for(0.0), 1);
+        assert_eq!(level_for(279.0), 1);
+        assert_eq!(level_for(280.0), 2);
+        assert_eq!(level_for(660.0), 3);
+        assert_eq!(level_for(1e9), 18);
+    }
+
+    #[test]
+    fn passive_gold_and_farm() {
+        let data = sample();
+        let snapshots = simulate(&data, &GamePlan { minutes: 2, ..plan(&[]) }).unwrap();
+        assert_eq!(snapshots.len(), 3);
+        assert_eq!(snapshots[0].gold, STARTING_GOLD);
+        assert!(close(snapshots[2].gold, STARTING_GOLD + 55.0 * 2.04));
+
+        let farming = simulate(&data, &GamePlan { minutes: 2, cs_per_minute: 6.0, experience_per_minute: 600.0, ..plan(&[]) }).unwrap();
+        assert!(close(farming[2].gold - snapshots[2].gold, 30.0 * 0.1 * MINION_GOLD));
+        assert!(close(farming[2].experience, 300.0));
+        assert_eq!(farming[2].level, 2);
+    }
+
+    #[test]
+    fn components_are_used_to_complete_items() {
+        let data = sample();
+        let snapshots = simulate(&data, &plan(&[1036, 1036, 3031])).unwrap();
+        assert_eq!(snapshots[0].bought, [1036]);
+        assert_eq!(snapshots[0].stats[StatKind::AttackDamage], 79.0);
+
+        let completed = snapshots.iter().find(|snapshot| snapshot.items == [3031]).unwrap();
+        let spent = completed.earned - completed.gold;
+        assert!(close(spent, 3450.0));
+        assert_eq!(snapshots.last().unwrap().items, [3031]);
+    }
+
+    #[test]
+    fn components_of_components_are_used() {
+        let data = sample();
+        let snapshots = simulate(&data, &plan(&[1036, 1036, 3071])).unwrap();
+        let completed = snapshots.iter().find(|snapshot| snapshot.items == [3071]).unwrap();
+        assert!(close(completed.earned - completed.gold, 3000.0));
+
+        let mut inventory = vec![3133, 1036];
+        assert_eq!(held_components(&data, 3071, &mut inventory), 1050.0);
+        assert_eq!(inventory, [1036]);
+    }
+
+    #[test]
+    fn full_inventories_skip_items() {
+        let data = sample();
+        let snapshots = simulate(&data, &plan(&[1036, 1036, 1036, 1036, 1036, 1036, 1029, 1033])).unwrap();
+        let last = snapshots.last().unwrap();
+        assert_eq!(last.items, [1036; 6]);
+        assert_eq!(snapshots.iter().flat_map(|snapshot| snapshot.skipped.clone()).collect::<Vec<_>>(), [1029, 1033]);
+    }
+
+    #[test]
+    fn support_quests_stop_paying() {
+        let data = sample();
+        let without = simulate(&data, &plan(&[])).unwrap();
+        let with = simulate(&data, &GamePlan { support: true, ..plan(&[]) }).unwrap();
+        assert!(close(with[30].earned - without[30].earned, SUPPORT_QUEST_GOLD));
+        assert!(close(with[1].earned - without[1].earned, 60.0 * 1.5));
+    }
+
+    #[test]
+    fn invalid_plans_are_errors() {
+        let data = sample();
+        assert!(simulate(&data, &GamePlan { champion: "Teemo".to_string(), ..plan(&[]) }).is_err());
+        assert!(simulate(&data, &plan(&[9999])).is_err());
+        assert!(simulate(&data, &GamePlan { minutes: 61, ..plan(&[]) }).is_err());
+    }
+}